@@ -1,6 +1,30 @@
 use crate::types::*;
+use crate::zobrist;
 use std::fmt::Display;
 
+const KNIGHT_JUMPS: [(isize, isize); 8] = [
+    (-2, -1),
+    (-2, 1),
+    (-1, -2),
+    (-1, 2),
+    (1, -2),
+    (1, 2),
+    (2, -1),
+    (2, 1),
+];
+const KING_STEPS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+const ROOK_DIRECTIONS: [(isize, isize); 4] = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+const BISHOP_DIRECTIONS: [(isize, isize); 4] = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+
 impl Board {
     pub fn initial_position() -> Self {
         Board::from_fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1").unwrap()
@@ -12,6 +36,8 @@ impl Board {
             turn: Color::White, // Default, lo sobreescribiremos leyendo el FEN
             castling_rights: CastlingRights::default(), // Default, lo sobreescribiremos leyendo el FEN
             en_passant_target: None,
+            hash: 0,
+            pawn_hash: 0,
         };
         // rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1
 
@@ -34,7 +60,7 @@ impl Board {
             if c == '/' {
                 rank -= 1;
                 file = 0;
-            } else if c.is_digit(10) {
+            } else if c.is_ascii_digit() {
                 file += c.to_digit(10).unwrap() as usize;
             } else {
                 let color = if c.is_uppercase() {
@@ -73,6 +99,9 @@ impl Board {
             board.en_passant_target = Some(rank * 8 + file);
         }
 
+        board.hash = zobrist::compute_hash(&board);
+        board.pawn_hash = zobrist::compute_pawn_hash(&board);
+
         Ok(board)
     }
 
@@ -165,39 +194,39 @@ impl Board {
         let mut moves = Vec::new();
 
         for (index, square) in self.squares.iter().enumerate() {
-            if let Some(piece) = square {
-                if piece.color == self.turn {
-                    match piece.piece_type {
-                        PieceType::Pawn => {
-                            self.gen_pawn_moves(index, &mut moves);
-                        }
-                        PieceType::Knight => {
-                            self.gen_knight_moves(index, &mut moves);
-                        }
-                        PieceType::Bishop => {
-                            let directions = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
-                            self.gen_sliding_moves(index, &mut moves, &directions);
-                        }
-                        PieceType::Rook => {
-                            let directions = [(-1, 0), (1, 0), (0, -1), (0, 1)];
-                            self.gen_sliding_moves(index, &mut moves, &directions);
-                        }
-                        PieceType::Queen => {
-                            let directions = [
-                                (-1, -1),
-                                (-1, 0),
-                                (-1, 1),
-                                (0, -1),
-                                (0, 1),
-                                (1, -1),
-                                (1, 0),
-                                (1, 1),
-                            ];
-                            self.gen_sliding_moves(index, &mut moves, &directions);
-                        }
-                        PieceType::King => {
-                            self.gen_king_moves(index, &mut moves);
-                        }
+            if let Some(piece) = square
+                && piece.color == self.turn
+            {
+                match piece.piece_type {
+                    PieceType::Pawn => {
+                        self.gen_pawn_moves(index, &mut moves);
+                    }
+                    PieceType::Knight => {
+                        self.gen_knight_moves(index, &mut moves);
+                    }
+                    PieceType::Bishop => {
+                        let directions = [(-1, -1), (-1, 1), (1, -1), (1, 1)];
+                        self.gen_sliding_moves(index, &mut moves, &directions);
+                    }
+                    PieceType::Rook => {
+                        let directions = [(-1, 0), (1, 0), (0, -1), (0, 1)];
+                        self.gen_sliding_moves(index, &mut moves, &directions);
+                    }
+                    PieceType::Queen => {
+                        let directions = [
+                            (-1, -1),
+                            (-1, 0),
+                            (-1, 1),
+                            (0, -1),
+                            (0, 1),
+                            (1, -1),
+                            (1, 0),
+                            (1, 1),
+                        ];
+                        self.gen_sliding_moves(index, &mut moves, &directions);
+                    }
+                    PieceType::King => {
+                        self.gen_king_moves(index, &mut moves);
                     }
                 }
             }
@@ -222,7 +251,7 @@ impl Board {
             let to_rank = rank as isize + delta_rank;
             let to_file = file as isize + delta_file;
 
-            if to_rank >= 0 && to_rank < 8 && to_file >= 0 && to_file < 8 {
+            if (0..8).contains(&to_rank) && (0..8).contains(&to_file) {
                 let to_index = self.coord_to_index(to_rank as Square, to_file as Square);
                 let target_square = self.squares[to_index];
 
@@ -257,7 +286,7 @@ impl Board {
             let to_rank = rank as isize + delta_rank;
             let to_file = file as isize + delta_file;
 
-            if to_rank >= 0 && to_rank < 8 && to_file >= 0 && to_file < 8 {
+            if (0..8).contains(&to_rank) && (0..8).contains(&to_file) {
                 let to_index = self.coord_to_index(to_rank as Square, to_file as Square);
                 let target_square = self.squares[to_index];
 
@@ -302,12 +331,10 @@ impl Board {
             if self.squares[d_sq].is_none()
                 && self.squares[c_sq].is_none()
                 && self.squares[b_sq].is_none()
+                && !self.is_square_attacked(index, opponent)
+                && !self.is_square_attacked(d_sq, opponent)
             {
-                if !self.is_square_attacked(index, opponent)
-                    && !self.is_square_attacked(d_sq, opponent)
-                {
-                    moves.push(Move::new(index, c_sq));
-                }
+                moves.push(Move::new(index, c_sq));
             }
         }
     }
@@ -324,7 +351,7 @@ impl Board {
             let mut to_rank = rank as isize + delta_rank;
             let mut to_file = file as isize + delta_file;
 
-            while to_rank >= 0 && to_rank < 8 && to_file >= 0 && to_file < 8 {
+            while (0..8).contains(&to_rank) && (0..8).contains(&to_file) {
                 let to_index = self.coord_to_index(to_rank as Square, to_file as Square);
                 let target_square = self.squares[to_index];
 
@@ -356,7 +383,7 @@ impl Board {
 
             // Movimiento hacia adelante
             let forward_rank = rank as isize + direction;
-            if forward_rank >= 0 && forward_rank < 8 {
+            if (0..8).contains(&forward_rank) {
                 let forward_index = self.coord_to_index(forward_rank as Square, file);
                 if self.squares[forward_index].is_none() {
                     if forward_rank as Square == promotion_rank {
@@ -389,9 +416,9 @@ impl Board {
             // Capturas diagonales
             for &delta_file in &[-1, 1] {
                 let capture_file = file as isize + delta_file;
-                if capture_file >= 0 && capture_file < 8 {
+                if (0..8).contains(&capture_file) {
                     let capture_rank = rank as isize + direction;
-                    if capture_rank >= 0 && capture_rank < 8 {
+                    if (0..8).contains(&capture_rank) {
                         let capture_index =
                             self.coord_to_index(capture_rank as Square, capture_file as Square);
                         if let Some(target_piece) = self.squares[capture_index] {
@@ -428,6 +455,8 @@ impl Board {
 
     pub fn make_move(&mut self, mv: &Move) {
         let piece = self.squares[mv.from].take().unwrap();
+        let old_castling_rights = self.castling_rights;
+        self.toggle_piece_key(piece, mv.from);
         let (from_rank, from_file) = self.index_to_coord(mv.from); // Necesitamos el origen
         let (to_rank, to_file) = self.index_to_coord(mv.to);
 
@@ -438,21 +467,28 @@ impl Board {
                 Color::Black => to_rank + 1,
             };
             let captured_pawn_index = self.coord_to_index(captured_pawn_rank, to_file);
-            self.squares[captured_pawn_index] = None; // Remover el peón capturado
+            // Remover el peón capturado
+            if let Some(captured_pawn) = self.squares[captured_pawn_index].take() {
+                self.toggle_piece_key(captured_pawn, captured_pawn_index);
+            }
         }
 
-        self.en_passant_target = None; // Resetear objetivo al paso
+        // Resetear objetivo al paso
+        if let Some(ep) = self.en_passant_target.take() {
+            self.hash ^= zobrist::en_passant_key(ep);
+        }
 
         // si el movimiento es un doble avance de peón, establecer el objetivo al paso
-        if piece.piece_type == PieceType::Pawn {
-            if (piece.color == Color::White && from_rank == 1 && to_rank == 3)
-                || (piece.color == Color::Black && from_rank == 6 && to_rank == 4)
-            {
-                // Movimiento doble de peón
-                let ep_rank = (from_rank + to_rank) / 2;
-                let ep_file = from_file;
-                self.en_passant_target = Some(self.coord_to_index(ep_rank, ep_file));
-            }
+        if piece.piece_type == PieceType::Pawn
+            && ((piece.color == Color::White && from_rank == 1 && to_rank == 3)
+                || (piece.color == Color::Black && from_rank == 6 && to_rank == 4))
+        {
+            // Movimiento doble de peón
+            let ep_rank = (from_rank + to_rank) / 2;
+            let ep_file = from_file;
+            let ep_square = self.coord_to_index(ep_rank, ep_file);
+            self.en_passant_target = Some(ep_square);
+            self.hash ^= zobrist::en_passant_key(ep_square);
         }
 
         // Si movemos el rey, perdemos ambos derechos de enroque
@@ -468,12 +504,12 @@ impl Board {
                     // Enroque corto
                     let rook_from = self.coord_to_index(to_rank, 7);
                     let rook_to = self.coord_to_index(to_rank, 5);
-                    self.squares[rook_to] = self.squares[rook_from].take();
+                    self.move_rook_for_castling(rook_from, rook_to);
                 } else if to_file == 2 {
                     // Enroque largo
                     let rook_from = self.coord_to_index(to_rank, 0);
                     let rook_to = self.coord_to_index(to_rank, 3);
-                    self.squares[rook_to] = self.squares[rook_from].take();
+                    self.move_rook_for_castling(rook_from, rook_to);
                 }
             }
         }
@@ -491,26 +527,35 @@ impl Board {
         }
 
         // Si capturamos una torre en su posición inicial, el oponente pierde el derecho de enroque correspondiente
-        if let Some(captured_piece) = self.squares[mv.to] {
-            if captured_piece.piece_type == PieceType::Rook {
-                if to_file == 0 {
-                    // Torre de la columna 'a'
-                    self.castling_rights
-                        .remove_castling_rights(captured_piece.color, false);
-                } else if to_file == 7 {
-                    // Torre de la columna 'h'
-                    self.castling_rights
-                        .remove_castling_rights(captured_piece.color, true);
-                }
+        if let Some(captured_piece) = self.squares[mv.to]
+            && captured_piece.piece_type == PieceType::Rook
+        {
+            if to_file == 0 {
+                // Torre de la columna 'a'
+                self.castling_rights
+                    .remove_castling_rights(captured_piece.color, false);
+            } else if to_file == 7 {
+                // Torre de la columna 'h'
+                self.castling_rights
+                    .remove_castling_rights(captured_piece.color, true);
             }
         }
 
+        if let Some(captured_piece) = self.squares[mv.to] {
+            self.toggle_piece_key(captured_piece, mv.to);
+        }
+
         // Mover la pieza (y manejar promoción si aplica)
-        if mv.promotion.is_some() {
-            let promo_piece = Piece::new(piece.color, mv.promotion.unwrap());
-            self.squares[mv.to] = Some(promo_piece);
-        } else {
-            self.squares[mv.to] = Some(piece);
+        let moved_piece = match mv.promotion {
+            Some(promotion) => Piece::new(piece.color, promotion),
+            None => piece,
+        };
+        self.squares[mv.to] = Some(moved_piece);
+        self.toggle_piece_key(moved_piece, mv.to);
+
+        if self.castling_rights != old_castling_rights {
+            self.hash ^= zobrist::castling_key(&old_castling_rights);
+            self.hash ^= zobrist::castling_key(&self.castling_rights);
         }
 
         // Cambiar el turno
@@ -518,14 +563,75 @@ impl Board {
             Color::White => Color::Black,
             Color::Black => Color::White,
         };
+        self.hash ^= zobrist::KEYS.side;
     }
 
-    fn find_king(&self, color: Color) -> Option<Square> {
+    // Quita o pone la clave de una pieza. Los peones entran además en el hash
+    // de peones, que identifica la estructura para la tabla de peones.
+    fn toggle_piece_key(&mut self, piece: Piece, square: Square) {
+        let key = zobrist::piece_key(piece, square);
+        self.hash ^= key;
+        if piece.piece_type == PieceType::Pawn {
+            self.pawn_hash ^= key;
+        }
+    }
+
+    fn move_rook_for_castling(&mut self, rook_from: Square, rook_to: Square) {
+        let rook = self.squares[rook_from].take();
+        if let Some(rook) = rook {
+            self.toggle_piece_key(rook, rook_from);
+            self.toggle_piece_key(rook, rook_to);
+        }
+        self.squares[rook_to] = rook;
+    }
+
+    // "Pasar" el turno sin mover (para la poda de movimiento nulo)
+    pub fn make_null_move(&mut self) {
+        if let Some(ep) = self.en_passant_target.take() {
+            self.hash ^= zobrist::en_passant_key(ep);
+        }
+        self.turn = self.turn.opposite();
+        self.hash ^= zobrist::KEYS.side;
+    }
+
+    // Si el bando tiene alguna pieza además de rey y peones. Sin ellas el
+    // zugzwang es habitual y no se puede suponer que mover siempre ayuda.
+    pub fn has_non_pawn_material(&self, color: Color) -> bool {
+        self.squares.iter().flatten().any(|piece| {
+            piece.color == color
+                && piece.piece_type != PieceType::Pawn
+                && piece.piece_type != PieceType::King
+        })
+    }
+
+    // La misma posición vista desde el otro bando: tablero volteado de arriba
+    // abajo y colores intercambiados. Una evaluación correcta solo cambia de signo.
+    pub fn mirror(&self) -> Board {
+        let mut board = self.clone();
+        for (square, piece) in self.squares.iter().enumerate() {
+            board.squares[square ^ 56] =
+                piece.map(|piece| Piece::new(piece.color.opposite(), piece.piece_type));
+        }
+        board.turn = self.turn.opposite();
+        board.castling_rights = CastlingRights {
+            white_kingside: self.castling_rights.black_kingside,
+            white_queenside: self.castling_rights.black_queenside,
+            black_kingside: self.castling_rights.white_kingside,
+            black_queenside: self.castling_rights.white_queenside,
+        };
+        board.en_passant_target = self.en_passant_target.map(|square| square ^ 56);
+        board.hash = zobrist::compute_hash(&board);
+        board.pawn_hash = zobrist::compute_pawn_hash(&board);
+        board
+    }
+
+    pub fn find_king(&self, color: Color) -> Option<Square> {
         for (index, square) in self.squares.iter().enumerate() {
-            if let Some(piece) = square {
-                if piece.piece_type == PieceType::King && piece.color == color {
-                    return Some(index);
-                }
+            if let Some(piece) = square
+                && piece.piece_type == PieceType::King
+                && piece.color == color
+            {
+                return Some(index);
             }
         }
         None
@@ -558,13 +664,13 @@ impl Board {
 
         for &delta_file in &[-1, 1] {
             let attack_file = file as isize + delta_file;
-            if pawn_attack_rank >= 0 && pawn_attack_rank < 8 && attack_file >= 0 && attack_file < 8
-            {
+            if (0..8).contains(&pawn_attack_rank) && (0..8).contains(&attack_file) {
                 let idx = self.coord_to_index(pawn_attack_rank as usize, attack_file as usize);
-                if let Some(piece) = self.squares[idx] {
-                    if piece.color == attacker && piece.piece_type == PieceType::Pawn {
-                        return true;
-                    }
+                if let Some(piece) = self.squares[idx]
+                    && piece.color == attacker
+                    && piece.piece_type == PieceType::Pawn
+                {
+                    return true;
                 }
             }
         }
@@ -583,12 +689,13 @@ impl Board {
         for (delta_rank, delta_file) in knight_jumps {
             let tr = rank as isize + delta_rank;
             let tf = file as isize + delta_file;
-            if tr >= 0 && tr < 8 && tf >= 0 && tf < 8 {
+            if (0..8).contains(&tr) && (0..8).contains(&tf) {
                 let idx = self.coord_to_index(tr as usize, tf as usize);
-                if let Some(piece) = self.squares[idx] {
-                    if piece.color == attacker && piece.piece_type == PieceType::Knight {
-                        return true;
-                    }
+                if let Some(piece) = self.squares[idx]
+                    && piece.color == attacker
+                    && piece.piece_type == PieceType::Knight
+                {
+                    return true;
                 }
             }
         }
@@ -607,12 +714,13 @@ impl Board {
         for (delta_rank, delta_file) in king_moves {
             let tr = rank as isize + delta_rank;
             let tf = file as isize + delta_file;
-            if tr >= 0 && tr < 8 && tf >= 0 && tf < 8 {
+            if (0..8).contains(&tr) && (0..8).contains(&tf) {
                 let idx = self.coord_to_index(tr as usize, tf as usize);
-                if let Some(piece) = self.squares[idx] {
-                    if piece.color == attacker && piece.piece_type == PieceType::King {
-                        return true;
-                    }
+                if let Some(piece) = self.squares[idx]
+                    && piece.color == attacker
+                    && piece.piece_type == PieceType::King
+                {
+                    return true;
                 }
             }
         }
@@ -623,19 +731,16 @@ impl Board {
         for (delta_rank, delta_file) in straight_dirs {
             let mut tr = rank as isize + delta_rank;
             let mut tf = file as isize + delta_file;
-            while tr >= 0 && tr < 8 && tf >= 0 && tf < 8 {
+            while (0..8).contains(&tr) && (0..8).contains(&tf) {
                 let idx = self.coord_to_index(tr as usize, tf as usize);
-                match self.squares[idx] {
-                    Some(piece) => {
-                        if piece.color == attacker
-                            && (piece.piece_type == PieceType::Rook
-                                || piece.piece_type == PieceType::Queen)
-                        {
-                            return true;
-                        }
-                        break; // Bloqueado por cualquier pieza
+                if let Some(piece) = self.squares[idx] {
+                    if piece.color == attacker
+                        && (piece.piece_type == PieceType::Rook
+                            || piece.piece_type == PieceType::Queen)
+                    {
+                        return true;
                     }
-                    None => {} // Sigue buscando
+                    break; // Bloqueado por cualquier pieza
                 }
                 tr += delta_rank;
                 tf += delta_file;
@@ -647,26 +752,170 @@ impl Board {
         for (delta_rank, delta_file) in diag_dirs {
             let mut tr = rank as isize + delta_rank;
             let mut tf = file as isize + delta_file;
-            while tr >= 0 && tr < 8 && tf >= 0 && tf < 8 {
+            while (0..8).contains(&tr) && (0..8).contains(&tf) {
                 let idx = self.coord_to_index(tr as usize, tf as usize);
-                match self.squares[idx] {
-                    Some(piece) => {
+                if let Some(piece) = self.squares[idx] {
+                    if piece.color == attacker
+                        && (piece.piece_type == PieceType::Bishop
+                            || piece.piece_type == PieceType::Queen)
+                    {
+                        return true;
+                    }
+                    break;
+                }
+                tr += delta_rank;
+                tf += delta_file;
+            }
+        }
+
+        false
+    }
+
+    // Devuelve las casillas de todas las piezas de `attacker` que atacan `square`.
+    // Las casillas marcadas en `removed` se tratan como vacías, así se ven los
+    // rayos X de las piezas que quedan detrás (lo necesita el SEE).
+    pub fn attackers_of(&self, square: Square, attacker: Color, removed: u64) -> Vec<Square> {
+        let (rank, file) = self.index_to_coord(square);
+        let mut attackers = Vec::new();
+
+        let piece_at = |idx: Square| {
+            if removed & (1 << idx) != 0 {
+                None
+            } else {
+                self.squares[idx]
+            }
+        };
+
+        // 1. Peones
+        let pawn_attack_rank = match attacker {
+            Color::White => rank as isize - 1,
+            Color::Black => rank as isize + 1,
+        };
+        for &delta_file in &[-1, 1] {
+            let attack_file = file as isize + delta_file;
+            if (0..8).contains(&pawn_attack_rank) && (0..8).contains(&attack_file) {
+                let idx = self.coord_to_index(pawn_attack_rank as usize, attack_file as usize);
+                if let Some(piece) = piece_at(idx)
+                    && piece.color == attacker
+                    && piece.piece_type == PieceType::Pawn
+                {
+                    attackers.push(idx);
+                }
+            }
+        }
+
+        // 2. Caballos y rey (saltos de una casilla)
+        let jumps = [
+            (PieceType::Knight, KNIGHT_JUMPS.as_slice()),
+            (PieceType::King, KING_STEPS.as_slice()),
+        ];
+        for (piece_type, deltas) in jumps {
+            for (delta_rank, delta_file) in deltas {
+                let tr = rank as isize + delta_rank;
+                let tf = file as isize + delta_file;
+                if (0..8).contains(&tr) && (0..8).contains(&tf) {
+                    let idx = self.coord_to_index(tr as usize, tf as usize);
+                    if let Some(piece) = piece_at(idx)
+                        && piece.color == attacker
+                        && piece.piece_type == piece_type
+                    {
+                        attackers.push(idx);
+                    }
+                }
+            }
+        }
+
+        // 3. Piezas deslizantes
+        let rays = [
+            (PieceType::Rook, ROOK_DIRECTIONS),
+            (PieceType::Bishop, BISHOP_DIRECTIONS),
+        ];
+        for (slider, directions) in rays {
+            for (delta_rank, delta_file) in directions {
+                let mut tr = rank as isize + delta_rank;
+                let mut tf = file as isize + delta_file;
+                while (0..8).contains(&tr) && (0..8).contains(&tf) {
+                    let idx = self.coord_to_index(tr as usize, tf as usize);
+                    if let Some(piece) = piece_at(idx) {
                         if piece.color == attacker
-                            && (piece.piece_type == PieceType::Bishop
-                                || piece.piece_type == PieceType::Queen)
+                            && (piece.piece_type == slider || piece.piece_type == PieceType::Queen)
                         {
-                            return true;
+                            attackers.push(idx);
                         }
                         break;
                     }
-                    None => {}
+                    tr += delta_rank;
+                    tf += delta_file;
                 }
-                tr += delta_rank;
-                tf += delta_file;
             }
         }
 
-        false
+        attackers
+    }
+
+    // Casillas que ataca la pieza de `square` (una por bit), ocupadas o no.
+    // Las piezas deslizantes se paran en la primera pieza que encuentran.
+    pub fn piece_attacks(&self, square: Square) -> u64 {
+        let Some(piece) = self.squares[square] else {
+            return 0;
+        };
+        let (rank, file) = self.index_to_coord(square);
+        let mut attacks = 0;
+
+        let mut add_steps = |deltas: &[(isize, isize)], sliding: bool| {
+            for (delta_rank, delta_file) in deltas {
+                let mut tr = rank as isize + delta_rank;
+                let mut tf = file as isize + delta_file;
+                while (0..8).contains(&tr) && (0..8).contains(&tf) {
+                    let idx = self.coord_to_index(tr as usize, tf as usize);
+                    attacks |= 1 << idx;
+                    if !sliding || self.squares[idx].is_some() {
+                        break;
+                    }
+                    tr += delta_rank;
+                    tf += delta_file;
+                }
+            }
+        };
+
+        match piece.piece_type {
+            PieceType::Pawn => {
+                let forward = match piece.color {
+                    Color::White => 1,
+                    Color::Black => -1,
+                };
+                add_steps(&[(forward, -1), (forward, 1)], false);
+            }
+            PieceType::Knight => add_steps(&KNIGHT_JUMPS, false),
+            PieceType::Bishop => add_steps(&BISHOP_DIRECTIONS, true),
+            PieceType::Rook => add_steps(&ROOK_DIRECTIONS, true),
+            PieceType::Queen => {
+                add_steps(&ROOK_DIRECTIONS, true);
+                add_steps(&BISHOP_DIRECTIONS, true);
+            }
+            PieceType::King => add_steps(&KING_STEPS, false),
+        }
+        attacks
+    }
+
+    // Pieza que captura un movimiento (incluye la captura al paso)
+    pub fn captured_piece(&self, mv: &Move) -> Option<PieceType> {
+        match self.squares[mv.to] {
+            Some(piece) => Some(piece.piece_type),
+            None => {
+                let is_pawn =
+                    self.squares[mv.from].is_some_and(|piece| piece.piece_type == PieceType::Pawn);
+                if is_pawn && Some(mv.to) == self.en_passant_target {
+                    Some(PieceType::Pawn)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+
+    pub fn is_capture(&self, mv: &Move) -> bool {
+        self.captured_piece(mv).is_some()
     }
 
     fn parse_move_string(&self, move_str: &str) -> Option<Move> {
@@ -714,16 +963,11 @@ impl Board {
 
         let legal_moves = self.generate_moves();
 
-        for legal_move in legal_moves {
-            if legal_move.from == parsed_move.from
+        legal_moves.into_iter().find(|legal_move| {
+            legal_move.from == parsed_move.from
                 && legal_move.to == parsed_move.to
                 && legal_move.promotion == parsed_move.promotion
-            {
-                return Some(legal_move);
-            }
-        }
-
-        None
+        })
     }
 
     pub fn coord_to_index(&self, rank: Square, file: Square) -> Square {
@@ -778,11 +1022,7 @@ impl Board {
             let nodes = board_copy.perft(depth - 1);
 
             // Imprimimos en formato "e2e4: 20"
-            println!(
-                "{}: {}",
-                m.to_string(),
-                nodes
-            );
+            println!("{}: {}", m, nodes);
             total_nodes += nodes;
         }
         println!("\nTotal Nodes: {}", total_nodes);
@@ -0,0 +1,360 @@
+use crate::{
+    evaluation::piece_value,
+    kpk::{KpkResult, probe_kpk},
+    types::{Board, Color, PieceType, Square, distance, relative_rank},
+};
+use std::{collections::HashMap, sync::OnceLock};
+
+// Puntuación de un final ganado conocido: por encima de cualquier evaluación
+// normal y lejos de las puntuaciones de mate
+pub const KNOWN_WIN: i32 = 10000;
+
+// Divisor de la evaluación normal en los finales con tablas probables
+pub const DRAWISH_SCALE: i32 = 8;
+
+// Piezas de cada bando sin contar el rey: peones, caballos, alfiles, torres y damas
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MaterialKey {
+    counts: [[u8; 5]; 2],
+}
+
+const PIECE_CHARS: [char; 5] = ['P', 'N', 'B', 'R', 'Q'];
+
+impl MaterialKey {
+    pub fn new(board: &Board) -> Self {
+        let mut counts = [[0; 5]; 2];
+        for piece in board.squares.iter().flatten() {
+            if piece.piece_type != PieceType::King {
+                counts[piece.color as usize][piece.piece_type as usize] += 1;
+            }
+        }
+        MaterialKey { counts }
+    }
+
+    // Firma como "KBNK": las piezas de las blancas y luego las de las negras,
+    // cada bando empezando por su rey
+    pub fn from_code(code: &str) -> Option<Self> {
+        let split = code.get(1..)?.find('K')? + 1;
+        let mut counts = [[0; 5]; 2];
+        for (side, part) in [&code[..split], &code[split..]].into_iter().enumerate() {
+            for c in part.strip_prefix('K')?.chars() {
+                let index = PIECE_CHARS.iter().position(|&p| p == c)?;
+                counts[side][index] += 1;
+            }
+        }
+        Some(MaterialKey { counts })
+    }
+
+    pub fn code(&self) -> String {
+        let mut code = String::new();
+        for side in self.counts {
+            code.push('K');
+            for (index, count) in side.iter().enumerate().rev() {
+                for _ in 0..*count {
+                    code.push(PIECE_CHARS[index]);
+                }
+            }
+        }
+        code
+    }
+
+    // El mismo material con los colores cambiados
+    pub fn flip(self) -> Self {
+        MaterialKey {
+            counts: [self.counts[1], self.counts[0]],
+        }
+    }
+
+    pub fn count(&self, color: Color, piece_type: PieceType) -> u8 {
+        self.counts[color as usize][piece_type as usize]
+    }
+
+    fn pieces(&self, color: Color) -> u8 {
+        self.counts[color as usize].iter().sum()
+    }
+
+    pub fn is_lone_king(&self, color: Color) -> bool {
+        self.pieces(color) == 0
+    }
+}
+
+// Tipos de final con evaluación propia
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Endgame {
+    // Nadie puede forzar el mate, o el final es de tablas teóricas
+    Draw,
+    // Material igualado que casi siempre acaba en tablas: se sigue evaluando
+    // normalmente (una pieza colgada o una enfilada cuentan), pero reducido
+    Drawish,
+    // Rey solo contra material suficiente: llevarlo al borde y acercar el rey
+    LoneKing,
+    // Alfil y caballo: hay que llevar al rey a una esquina del color del alfil
+    Kbnk,
+    // Rey y peón contra rey: resultado exacto de la bitbase
+    Kpk,
+}
+
+// Finales por firma exacta, con el bando fuerte en blancas
+const ENDGAMES: [(&str, Endgame); 13] = [
+    ("KK", Endgame::Draw),
+    ("KNK", Endgame::Draw),
+    ("KBK", Endgame::Draw),
+    ("KNNK", Endgame::Draw),
+    ("KNKN", Endgame::Draw),
+    ("KBKN", Endgame::Draw),
+    ("KBKB", Endgame::Draw),
+    ("KRKR", Endgame::Drawish),
+    ("KQKQ", Endgame::Drawish),
+    ("KQK", Endgame::LoneKing),
+    ("KRK", Endgame::LoneKing),
+    ("KBNK", Endgame::Kbnk),
+    ("KPK", Endgame::Kpk),
+];
+
+// Tabla de finales indexada por la firma en los dos sentidos: el final y el
+// color del bando fuerte
+fn endgame_table() -> &'static HashMap<MaterialKey, (Endgame, Color)> {
+    static TABLE: OnceLock<HashMap<MaterialKey, (Endgame, Color)>> = OnceLock::new();
+    TABLE.get_or_init(|| {
+        let mut table = HashMap::new();
+        for (code, endgame) in ENDGAMES {
+            let key = MaterialKey::from_code(code).expect("Firma de final inválida");
+            table.insert(key.flip(), (endgame, Color::Black));
+            table.insert(key, (endgame, Color::White));
+        }
+        table
+    })
+}
+
+// Lo que se sabe de la posición con una sola consulta a la tabla
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    // Final conocido con evaluación propia, desde el punto de vista de las blancas
+    Score(i32),
+    // Divisor de la evaluación normal: 1 salvo en los finales con tablas probables
+    Scale(i32),
+}
+
+// Todos los finales conocidos tienen un rey solo o como mucho dos piezas
+// además de los reyes: en cuanto hay más y los dos bandos tienen alguna, la
+// posición no es uno de ellos y no hace falta construir la firma
+fn may_be_known(board: &Board) -> bool {
+    let mut pieces = [0; 2];
+    for piece in board.squares.iter().flatten() {
+        if piece.piece_type != PieceType::King {
+            pieces[piece.color as usize] += 1;
+            if pieces[0] > 0 && pieces[1] > 0 && pieces[0] + pieces[1] > 2 {
+                return false;
+            }
+        }
+    }
+    true
+}
+
+// Final conocido de la posición, si lo es, con el color del bando fuerte
+pub fn probe(board: &Board) -> Option<(Endgame, Color)> {
+    if !may_be_known(board) {
+        return None;
+    }
+    let key = MaterialKey::new(board);
+    if let Some(entry) = endgame_table().get(&key) {
+        return Some(*entry);
+    }
+
+    // Familias que no caben en una firma fija
+    for strong in [Color::White, Color::Black] {
+        let weak = strong.opposite();
+        if !key.is_lone_king(weak) {
+            continue;
+        }
+        if can_force_mate(board, &key, strong) {
+            return Some((Endgame::LoneKing, strong));
+        }
+        if is_wrong_bishop_draw(board, &key, strong) {
+            return Some((Endgame::Draw, strong));
+        }
+    }
+    None
+}
+
+// Evaluación propia o divisor de la evaluación normal
+pub fn verdict(board: &Board) -> Verdict {
+    match probe(board) {
+        Some((Endgame::Drawish, _)) => Verdict::Scale(DRAWISH_SCALE),
+        Some((endgame, strong)) => {
+            known_score(board, endgame, strong).map_or(Verdict::Scale(1), Verdict::Score)
+        }
+        None => Verdict::Scale(1),
+    }
+}
+
+// Evaluación de un final conocido desde el punto de vista de las blancas. Los
+// finales con tablas probables no tienen una propia: ver `scale`.
+pub fn evaluate(board: &Board) -> Option<i32> {
+    match verdict(board) {
+        Verdict::Score(score) => Some(score),
+        Verdict::Scale(_) => None,
+    }
+}
+
+// Divisor de la evaluación normal: 1 salvo en los finales con tablas probables
+pub fn scale(board: &Board) -> i32 {
+    match verdict(board) {
+        Verdict::Score(_) => 1,
+        Verdict::Scale(scale) => scale,
+    }
+}
+
+fn known_score(board: &Board, endgame: Endgame, strong: Color) -> Option<i32> {
+    let weak = strong.opposite();
+    let (strong_king, weak_king) = (board.find_king(strong)?, board.find_king(weak)?);
+
+    let score = match endgame {
+        Endgame::Draw => return Some(0),
+        Endgame::Drawish => return None,
+        Endgame::LoneKing => {
+            KNOWN_WIN
+                + material(board, strong)
+                + push_to_edge(weak_king)
+                + push_close(strong_king, weak_king)
+        }
+        Endgame::Kbnk => {
+            let bishop = find_piece(board, strong, PieceType::Bishop)?;
+            let knight = find_piece(board, strong, PieceType::Knight)?;
+            // El caballo también tiene que acercarse para cerrar la red de mate
+            KNOWN_WIN
+                + material(board, strong)
+                + push_close(strong_king, weak_king)
+                + push_close(knight, weak_king) / 2
+                + push_to_bishop_corner(weak_king, bishop)
+        }
+        Endgame::Kpk => match probe_kpk(board)? {
+            KpkResult::Draw => return Some(0),
+            // Cuanto más avanzado el peón, antes corona
+            KpkResult::Win => {
+                let pawn = find_piece(board, strong, PieceType::Pawn)?;
+                let rank = relative_rank(pawn, strong) as i32;
+                KNOWN_WIN + piece_value(PieceType::Pawn) + 10 * rank
+            }
+        },
+    };
+
+    Some(match strong {
+        Color::White => score,
+        Color::Black => -score,
+    })
+}
+
+// Material con el que se fuerza el mate al rey solo: dama, torre, alfil y
+// caballo, o alfiles de los dos colores
+fn can_force_mate(board: &Board, key: &MaterialKey, color: Color) -> bool {
+    let count = |piece_type| key.count(color, piece_type);
+    if count(PieceType::Queen) > 0
+        || count(PieceType::Rook) > 0
+        || (count(PieceType::Bishop) > 0 && count(PieceType::Knight) > 0)
+    {
+        return true;
+    }
+    let mut bishop_colors = [false; 2];
+    for (square, piece) in board.squares.iter().enumerate() {
+        if piece.is_some_and(|p| p.color == color && p.piece_type == PieceType::Bishop) {
+            bishop_colors[is_light_square(square) as usize] = true;
+        }
+    }
+    bishop_colors == [true, true]
+}
+
+fn find_piece(board: &Board, color: Color, piece_type: PieceType) -> Option<Square> {
+    board
+        .squares
+        .iter()
+        .position(|piece| piece.is_some_and(|p| p.color == color && p.piece_type == piece_type))
+}
+
+fn material(board: &Board, color: Color) -> i32 {
+    board
+        .squares
+        .iter()
+        .flatten()
+        .filter(|piece| piece.color == color)
+        .map(|piece| piece_value(piece.piece_type))
+        .sum()
+}
+
+fn is_light_square(square: Square) -> bool {
+    (square / 8 + square % 8) % 2 == 1
+}
+
+// Más cuanto más cerca del borde esté el rey débil (lo máximo en las esquinas)
+fn push_to_edge(square: Square) -> i32 {
+    let file = (square % 8).min(7 - square % 8) as i32;
+    let rank = (square / 8).min(7 - square / 8) as i32;
+    90 - (7 * file * file / 2 + 7 * rank * rank / 2)
+}
+
+// Más cuanto más cerca estén los reyes: el fuerte tiene que ayudar a dar mate
+fn push_close(a: Square, b: Square) -> i32 {
+    140 - 20 * distance(a, b) as i32
+}
+
+// Con alfil y caballo solo se da mate en las esquinas del color del alfil:
+// cuanto más cerca de una de ellas, mejor. El borde no basta. La distancia
+// de Manhattan sola deja mesetas por las que la búsqueda da vueltas; con la
+// distancia de rey la pendiente hacia la esquina no se interrumpe.
+fn push_to_bishop_corner(weak_king: Square, bishop: Square) -> i32 {
+    let corners: [Square; 2] = if is_light_square(bishop) {
+        [7, 56] // h1 y a8
+    } else {
+        [0, 63] // a1 y h8
+    };
+    let nearest = |metric: fn(Square, Square) -> usize| {
+        corners
+            .iter()
+            .map(|&corner| metric(weak_king, corner))
+            .min()
+            .unwrap_or(0) as i32
+    };
+    push_to_edge(weak_king) + 60 * (14 - nearest(manhattan)) + 40 * (7 - nearest(distance))
+}
+
+fn manhattan(a: Square, b: Square) -> usize {
+    (a / 8).abs_diff(b / 8) + (a % 8).abs_diff(b % 8)
+}
+
+// Alfil y peones de torre contra rey: si el alfil no controla la casilla de
+// coronación y el rey débil llega a la esquina, es tablas
+fn is_wrong_bishop_draw(board: &Board, key: &MaterialKey, strong: Color) -> bool {
+    let pawns = key.count(strong, PieceType::Pawn);
+    if pawns == 0 || key.count(strong, PieceType::Bishop) != 1 || key.pieces(strong) != pawns + 1 {
+        return false;
+    }
+
+    let mut bishop = None;
+    let mut pawn_files = 0u8;
+    for (square, piece) in board.squares.iter().enumerate() {
+        match piece {
+            Some(p) if p.color == strong && p.piece_type == PieceType::Bishop => {
+                bishop = Some(square)
+            }
+            Some(p) if p.color == strong && p.piece_type == PieceType::Pawn => {
+                pawn_files |= 1 << (square % 8)
+            }
+            _ => {}
+        }
+    }
+    let file = match pawn_files {
+        0b0000_0001 => 0,
+        0b1000_0000 => 7,
+        _ => return false,
+    };
+    let queening_square = match strong {
+        Color::White => 56 + file,
+        Color::Black => file,
+    };
+
+    let (Some(bishop), Some(weak_king)) = (bishop, board.find_king(strong.opposite())) else {
+        return false;
+    };
+    is_light_square(bishop) != is_light_square(queening_square)
+        && distance(weak_king, queening_square) <= 1
+}
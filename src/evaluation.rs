@@ -1,4 +1,16 @@
-use crate::types::{Board, Color, PieceType};
+use crate::{
+    endgame::{self, Verdict},
+    king_safety::side_safety,
+    mobility::side_activity,
+    params::{Accumulator, Coefficients, PARAMS, ScoreSum, Side},
+    pawns::{PawnEntry, PawnTable, analyze, passed_pawns_for, pawn_structure, side_pawn_structure},
+    psqt::{side_material, side_piece_squares},
+    types::{Board, Color, PieceType},
+};
+use std::{
+    fmt,
+    ops::{Add, AddAssign, Mul, Neg, Sub, SubAssign},
+};
 
 const PAWN_VALUE: i32 = 100;
 const KNIGHT_VALUE: i32 = 320;
@@ -6,35 +18,350 @@ const BISHOP_VALUE: i32 = 330;
 const ROOK_VALUE: i32 = 500;
 const QUEEN_VALUE: i32 = 900;
 
+// Fase de la partida según el material que queda sin contar peones: 24 con
+// todas las piezas (medio juego puro) y 0 con solo reyes y peones (final puro)
+pub const MAX_PHASE: i32 = 24;
+const PHASE_WEIGHTS: [i32; 6] = [0, 1, 1, 2, 4, 0];
+
+// Valores fijos para SEE, la ordenación y la poda delta
+pub fn piece_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn => PAWN_VALUE,
+        PieceType::Knight => KNIGHT_VALUE,
+        PieceType::Bishop => BISHOP_VALUE,
+        PieceType::Rook => ROOK_VALUE,
+        PieceType::Queen => QUEEN_VALUE,
+        PieceType::King => 0, // El rey no tiene valor material
+    }
+}
+
+// Puntuación doble: una para el medio juego y otra para el final. Se mezclan
+// según la fase al final de la evaluación.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Score {
+    pub const ZERO: Score = Score::new(0, 0);
+
+    pub const fn new(mg: i32, eg: i32) -> Self {
+        Score { mg, eg }
+    }
+
+    // Interpolación lineal entre medio juego (fase máxima) y final (fase 0)
+    pub fn taper(self, phase: i32) -> i32 {
+        let phase = phase.clamp(0, MAX_PHASE);
+        (self.mg * phase + self.eg * (MAX_PHASE - phase)) / MAX_PHASE
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        Score::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, other: Score) -> Score {
+        Score::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score::new(-self.mg, -self.eg)
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Score;
+
+    fn mul(self, factor: i32) -> Score {
+        Score::new(self.mg * factor, self.eg * factor)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for Score {
+    fn sub_assign(&mut self, other: Score) {
+        *self = *self - other;
+    }
+}
+
+pub fn game_phase(board: &Board) -> i32 {
+    let phase: i32 = board
+        .squares
+        .iter()
+        .flatten()
+        .map(|piece| PHASE_WEIGHTS[piece.piece_type as usize])
+        .sum();
+    // Con promociones puede pasarse del máximo
+    phase.min(MAX_PHASE)
+}
+
+// Evaluación completa desde el punto de vista de las blancas. Analiza la
+// estructura de peones desde cero; la búsqueda usa `evaluate_cached`.
 pub fn evaluate(board: &Board) -> i32 {
-    let mut score = 0;
-
-    for opt_piece in board.squares.iter() {
-        if let Some(piece) = opt_piece {
-            let piece_value = match piece.piece_type {
-                PieceType::Pawn => PAWN_VALUE,
-                PieceType::Knight => KNIGHT_VALUE,
-                PieceType::Bishop => BISHOP_VALUE,
-                PieceType::Rook => ROOK_VALUE,
-                PieceType::Queen => QUEEN_VALUE,
-                _ => 0, // El rey no tiene valor material
-            };
-
-            if piece.color == Color::White {
-                score += piece_value;
-            } else {
-                score -= piece_value;
+    evaluate_with_pawns(board, &analyze(board))
+}
+
+// Igual que `evaluate`, pero con la estructura de peones de la tabla
+pub fn evaluate_cached(board: &Board, pawns: &mut PawnTable) -> i32 {
+    evaluate_with_pawns(board, &pawns.probe(board))
+}
+
+fn evaluate_with_pawns(board: &Board, pawns: &PawnEntry) -> i32 {
+    // Los finales conocidos tienen su propia evaluación
+    let scale = match endgame::verdict(board) {
+        Verdict::Score(score) => return score,
+        Verdict::Scale(scale) => scale,
+    };
+    let mut sum = ScoreSum::new(&PARAMS);
+    sum.score = pawns.score;
+    piece_terms(board, pawns, &mut sum);
+    sum.score.taper(game_phase(board)) / scale
+}
+
+// Todos los términos salvo la estructura de peones, que viene de la tabla
+fn piece_terms<A: Accumulator>(board: &Board, pawns: &PawnEntry, acc: &mut A) {
+    for (color, sign) in [(Color::White, 1), (Color::Black, -1)] {
+        let mut side = Side::new(acc, sign);
+        side_material(board, color, &mut side);
+        side_piece_squares(board, color, &mut side);
+        passed_pawns_for(board, pawns, color, &mut side);
+        side_safety(board, color, &mut side);
+        side_activity(board, color, &mut side);
+    }
+}
+
+// Cuántas veces entra cada parámetro en la evaluación de la posición (desde
+// las blancas): la evaluación es la suma de cada parámetro por su cuenta,
+// mezclada por fase
+pub fn coefficients(board: &Board) -> Coefficients {
+    let mut coefficients = Coefficients::default();
+    let pawns = PawnEntry {
+        key: board.pawn_hash,
+        score: Score::ZERO,
+        passed: pawn_structure(board, &mut coefficients),
+    };
+    piece_terms(board, &pawns, &mut coefficients);
+    coefficients
+}
+
+// Material y tablas pieza-casilla de todas las piezas, rey incluido
+fn pieces(board: &Board) -> Score {
+    let mut sum = ScoreSum::new(&PARAMS);
+    for (color, sign) in [(Color::White, 1), (Color::Black, -1)] {
+        side_material(board, color, &mut Side::new(&mut sum, sign));
+        side_piece_squares(board, color, &mut Side::new(&mut sum, sign));
+    }
+    sum.score
+}
+
+// Términos de la evaluación, en el orden en que se muestran en el desglose
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Term {
+    Material,
+    PieceSquares,
+    PawnStructure,
+    PassedPawns,
+    KingSafety,
+    Mobility,
+}
+
+pub const TERMS: [Term; 6] = [
+    Term::Material,
+    Term::PieceSquares,
+    Term::PawnStructure,
+    Term::PassedPawns,
+    Term::KingSafety,
+    Term::Mobility,
+];
+
+impl Term {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Term::Material => "Material",
+            Term::PieceSquares => "Tablas pieza-casilla",
+            Term::PawnStructure => "Estructura de peones",
+            Term::PassedPawns => "Peones pasados",
+            Term::KingSafety => "Seguridad del rey",
+            Term::Mobility => "Movilidad y piezas",
+        }
+    }
+}
+
+// Desglose de la evaluación: cada término de cada bando (positivo si le
+// favorece), la fase y el resultado. Es la misma cuenta que `evaluate`.
+#[derive(Debug, Clone)]
+pub struct EvalTrace {
+    // [término][blancas, negras]
+    pub terms: [[Score; 2]; TERMS.len()],
+    pub phase: i32,
+    pub turn: Color,
+    // Evaluación de un final conocido, que sustituye a la suma de términos
+    pub endgame: Option<i32>,
+    // Divisor de la suma de términos en los finales con tablas probables
+    pub scale: i32,
+}
+
+impl EvalTrace {
+    pub fn term(&self, term: Term) -> [Score; 2] {
+        self.terms[term as usize]
+    }
+
+    // Suma de blancas menos negras antes de mezclar por fase
+    pub fn total(&self) -> Score {
+        self.terms
+            .iter()
+            .fold(Score::ZERO, |total, [white, black]| total + *white - *black)
+    }
+
+    // Puntuación final desde el punto de vista de las blancas
+    pub fn score(&self) -> i32 {
+        self.endgame
+            .unwrap_or_else(|| self.total().taper(self.phase) / self.scale)
+    }
+
+    // Puntuación final desde el punto de vista del bando que mueve
+    pub fn side_to_move_score(&self) -> i32 {
+        match self.turn {
+            Color::White => self.score(),
+            Color::Black => -self.score(),
+        }
+    }
+}
+
+pub fn trace(board: &Board) -> EvalTrace {
+    let pawns = analyze(board);
+    let mut terms = [[Score::ZERO; 2]; TERMS.len()];
+
+    for (side, color) in [Color::White, Color::Black].into_iter().enumerate() {
+        for term in TERMS {
+            let mut sum = ScoreSum::new(&PARAMS);
+            match term {
+                Term::Material => side_material(board, color, &mut sum),
+                Term::PieceSquares => side_piece_squares(board, color, &mut sum),
+                Term::PawnStructure => side_pawn_structure(board, color, &mut sum),
+                Term::PassedPawns => passed_pawns_for(board, &pawns, color, &mut sum),
+                Term::KingSafety => side_safety(board, color, &mut sum),
+                Term::Mobility => side_activity(board, color, &mut sum),
             }
+            terms[term as usize][side] = sum.score;
         }
     }
 
-    score
+    let (endgame, scale) = match endgame::verdict(board) {
+        Verdict::Score(score) => (Some(score), 1),
+        Verdict::Scale(scale) => (None, scale),
+    };
+    EvalTrace {
+        terms,
+        phase: game_phase(board),
+        turn: board.turn,
+        endgame,
+        scale,
+    }
+}
+
+impl fmt::Display for EvalTrace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let separator = format!("{:-<22}+{:-<15}+{:-<15}+{:-<15}", "", "", "", "");
+        writeln!(
+            f,
+            "{:>21} | {:^13} | {:^13} | {:^13}",
+            "Término", "Blancas", "Negras", "Total"
+        )?;
+        writeln!(
+            f,
+            "{:>21} | {:>6} {:>6} | {:>6} {:>6} | {:>6} {:>6}",
+            "", "MG", "EG", "MG", "EG", "MG", "EG"
+        )?;
+        writeln!(f, "{}", separator)?;
+        for term in TERMS {
+            let [white, black] = self.term(term);
+            let total = white - black;
+            writeln!(
+                f,
+                "{:>21} | {:>6} {:>6} | {:>6} {:>6} | {:>6} {:>6}",
+                term.name(),
+                white.mg,
+                white.eg,
+                black.mg,
+                black.eg,
+                total.mg,
+                total.eg
+            )?;
+        }
+        writeln!(f, "{}", separator)?;
+        let total = self.total();
+        writeln!(
+            f,
+            "{:>21} | {:>13} | {:>13} | {:>6} {:>6}",
+            "Total", "", "", total.mg, total.eg
+        )?;
+        writeln!(f)?;
+        writeln!(
+            f,
+            "Fase: {}/{} (24 = medio juego, 0 = final)",
+            self.phase, MAX_PHASE
+        )?;
+        if let Some(score) = self.endgame {
+            writeln!(f, "Final conocido: {} cp en lugar de los términos", score)?;
+        }
+        if self.scale > 1 {
+            writeln!(
+                f,
+                "Tablas probables: la suma se divide entre {}",
+                self.scale
+            )?;
+        }
+        writeln!(f, "Evaluación (blancas): {} cp", self.score())?;
+        write!(
+            f,
+            "Evaluación (bando que mueve, {}): {} cp",
+            match self.turn {
+                Color::White => "blancas",
+                Color::Black => "negras",
+            },
+            self.side_to_move_score()
+        )
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::types::Board;
+    use crate::{
+        psqt::{material, psqt},
+        types::{Board, Piece, Square},
+    };
+
+    // Material más tabla de una pieza, con signo desde el punto de vista blanco
+    fn piece_term(color: Color, piece_type: PieceType, square: Square) -> Score {
+        let value = material(piece_type) + psqt(Piece::new(color, piece_type), square);
+        match color {
+            Color::White => value,
+            Color::Black => -value,
+        }
+    }
+
+    fn eval_fen(fen: &str) -> i32 {
+        evaluate(&Board::from_fen(fen).expect("FEN inválido"))
+    }
 
     #[test]
     fn test_eval_start_position() {
@@ -42,29 +369,45 @@ mod tests {
         let board = Board::initial_position();
         let score = evaluate(&board);
         assert_eq!(score, 0, "La posición inicial debería tener score 0");
+        assert_eq!(game_phase(&board), MAX_PHASE);
+    }
+
+    fn pieces_fen(fen: &str) -> Score {
+        pieces(&Board::from_fen(fen).expect("FEN inválido"))
     }
 
     #[test]
     fn test_eval_white_pawn_advantage() {
         // Blancas tienen un peón extra en e4
-        // FEN: Reyes y un peón blanco
-        let fen = "8/8/8/8/4P3/8/8/k3K3 w - - 0 1";
-        let board = Board::from_fen(fen).expect("FEN inválido");
-        let score = evaluate(&board);
+        let kings = pieces_fen("8/8/8/8/8/8/8/k3K3 w - - 0 1");
+        let pieces = pieces_fen("8/8/8/8/4P3/8/8/k3K3 w - - 0 1");
+        assert_eq!(
+            pieces - kings,
+            piece_term(Color::White, PieceType::Pawn, 28)
+        );
 
-        assert_eq!(score, PAWN_VALUE, "Blancas deberían ganar por un peón");
+        let score = eval_fen("8/8/8/8/4P3/8/8/k3K3 w - - 0 1");
+        let without_pawn = eval_fen("8/8/8/8/8/8/8/k3K3 w - - 0 1");
+        assert!(
+            score - without_pawn >= PAWN_VALUE / 2,
+            "Blancas deberían ganar por un peón"
+        );
     }
 
     #[test]
     fn test_eval_black_rook_advantage() {
-        // Negras tienen una torre extra
-        // FEN: Reyes y una torre negra en a8
+        // Negras tienen una torre extra en a8
         let fen = "r7/8/8/8/8/8/8/k3K3 w - - 0 1";
-        let board = Board::from_fen(fen).expect("FEN inválido");
-        let score = evaluate(&board);
+        let rook = piece_term(Color::Black, PieceType::Rook, 56);
+        let king_terms = piece_term(Color::White, PieceType::King, 4)
+            + piece_term(Color::Black, PieceType::King, 0);
+        assert_eq!(pieces_fen(fen), king_terms + rook);
 
-        assert_eq!(
-            score, -ROOK_VALUE,
+        // La torre suma 2 a la fase
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(game_phase(&board), 2);
+        assert!(
+            evaluate(&board) < -ROOK_VALUE / 2,
             "Negras deberían ganar por una torre (score negativo)"
         );
     }
@@ -73,41 +416,105 @@ mod tests {
     fn test_eval_material_imbalance() {
         // Blanco: Rey + Dama. Negro: Rey + Torre + Alfil.
         let fen = "8/8/8/2b5/2r5/8/3Q4/k3K3 w - - 0 1";
-
         let board = Board::from_fen(fen).expect("FEN inválido");
-        let score = evaluate(&board);
 
-        let expected_white = QUEEN_VALUE;
-        let expected_black = ROOK_VALUE + BISHOP_VALUE;
-        let expected_diff = expected_white - expected_black;
+        let expected = piece_term(Color::White, PieceType::King, 4)
+            + piece_term(Color::White, PieceType::Queen, 11)
+            + piece_term(Color::Black, PieceType::King, 0)
+            + piece_term(Color::Black, PieceType::Rook, 26)
+            + piece_term(Color::Black, PieceType::Bishop, 34);
 
+        assert_eq!(game_phase(&board), 4 + 2 + 1);
         assert_eq!(
-            score, expected_diff,
+            pieces(&board),
+            expected,
             "El cálculo de desequilibrio material falló"
         );
     }
 
     #[test]
     fn test_eval_symmetric_position() {
-        let fen = "8/8/8/4p3/4P3/8/8/k3K3 w - - 0 1";
-        let board = Board::from_fen(fen).expect("FEN inválido");
-        let score = evaluate(&board);
+        let fen = "4k3/8/8/4p3/4P3/8/8/4K3 w - - 0 1";
+        let score = eval_fen(fen);
 
         assert_eq!(score, 0, "Material igual y simétrico debería ser 0");
     }
 
+    #[test]
+    fn test_eval_color_symmetry() {
+        // Voltear el tablero e intercambiar colores solo cambia el signo
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/4P3/8/PPPP1PPP/RNBQKBNR b KQkq e3 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/8/8/2b5/2r5/8/3Q4/k3K3 w - - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+        ];
+        for fen in fens {
+            let board = Board::from_fen(fen).expect("FEN inválido");
+            assert_eq!(evaluate(&board), -evaluate(&board.mirror()), "{}", fen);
+        }
+    }
+
+    #[test]
+    fn test_eval_tapered_between_phases() {
+        // Una mezcla siempre cae entre el valor de medio juego y el de final
+        let score = Score::new(100, -50);
+        assert_eq!(score.taper(MAX_PHASE), 100);
+        assert_eq!(score.taper(0), -50);
+        assert_eq!(score.taper(MAX_PHASE / 2), 25);
+    }
+
+    #[test]
+    fn test_trace_matches_evaluate() {
+        let fens = [
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            "6k1/5p1p/8/8/5n2/6q1/5P1P/6K1 b - - 0 1",
+        ];
+        for fen in fens {
+            let board = Board::from_fen(fen).expect("FEN inválido");
+            let trace = trace(&board);
+            assert_eq!(trace.score(), evaluate(&board), "{}", fen);
+            assert_eq!(trace.phase, game_phase(&board));
+        }
+    }
+
+    #[test]
+    fn test_trace_terms_by_side() {
+        // Negras con una torre de más y mueven ellas: el desglose lo atribuye
+        // a las negras y la puntuación del bando que mueve es positiva
+        let board = Board::from_fen("r3k3/8/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        let trace = trace(&board);
+        let [white, black] = trace.term(Term::Material);
+        assert_eq!(white, Score::ZERO);
+        assert_eq!(black, material(PieceType::Rook));
+        assert!(trace.score() < 0);
+        assert_eq!(trace.side_to_move_score(), -trace.score());
+
+        let table = trace.to_string();
+        for term in TERMS {
+            assert!(table.contains(term.name()));
+        }
+        assert!(table.contains(&format!("{} cp", trace.side_to_move_score())));
+    }
+
     #[test]
     fn test_eval_complex_capture() {
-        // Blancas faltan: 1 Peón.
-        // Negras faltan: 1 Caballo.
-        // Ventaja blanca: Caballo - Peón = 320 - 100 = +220.
+        // Blancas faltan: 1 Peón (e2).
+        // Negras faltan: 1 Caballo (g8).
+        // El resto es simétrico y se anula; la fase baja a 23.
 
         // FEN startpos sin peón blanco e2 y sin caballo negro g8
         let fen = "rnbqkb1r/pppppppp/8/8/8/8/PPPP1PPP/RNBQKBNR w KQkq - 0 1";
-
         let board = Board::from_fen(fen).expect("FEN inválido");
-        let score = evaluate(&board);
 
-        assert_eq!(score, KNIGHT_VALUE - PAWN_VALUE, "Fallo en conteo complejo");
+        let missing = piece_term(Color::Black, PieceType::Knight, 62)
+            + piece_term(Color::White, PieceType::Pawn, 12);
+        assert_eq!(pieces(&board), -missing, "Fallo en conteo complejo");
+        assert_eq!(game_phase(&board), 23);
+
+        let score = evaluate(&board);
+        assert!(score > 0);
     }
 }
@@ -0,0 +1,138 @@
+use crate::{
+    evaluation::Score,
+    params::{Accumulator, PARAMS, SAFETY_TABLE, ScoreSum, Side, idx},
+    types::{Board, Color, PieceType, Square, relative_rank},
+};
+
+// Todos los términos son solo de medio juego (el ajuste no les da valor de
+// final): al mezclar por fase desaparecen en el final, donde el rey tiene que
+// salir a jugar.
+
+// Unidades de ataque por cada casilla de la zona del rey que ataca cada pieza
+// (caballo, alfil, torre, dama)
+const ATTACK_WEIGHTS: [i32; 6] = [0, 2, 2, 3, 5, 0];
+
+// Un solo atacante no basta para un ataque peligroso
+const MIN_ATTACKERS: u32 = 2;
+
+// Seguridad de los dos reyes desde el punto de vista de las blancas
+pub fn king_safety(board: &Board) -> Score {
+    let mut sum = ScoreSum::new(&PARAMS);
+    side_safety(board, Color::White, &mut sum);
+    side_safety(board, Color::Black, &mut Side::new(&mut sum, -1));
+    sum.score
+}
+
+pub fn side_safety<A: Accumulator>(board: &Board, color: Color, acc: &mut A) {
+    let Some(king) = board.find_king(color) else {
+        return;
+    };
+    add_pawn_cover(board, king, color, acc);
+
+    // Con varios atacantes, la penalización crece de forma no lineal con las
+    // unidades de ataque
+    let (attackers, units) = attack_units(board, king, color);
+    if attackers >= MIN_ATTACKERS {
+        let entry = (units as usize).min(SAFETY_TABLE.len() - 1);
+        acc.add(idx::SAFETY_TABLE + entry, 1);
+    }
+}
+
+// Escudo, tormenta y columnas abiertas en la columna del rey y sus vecinas
+pub fn pawn_cover(board: &Board, king: Square, color: Color) -> Score {
+    let mut sum = ScoreSum::new(&PARAMS);
+    add_pawn_cover(board, king, color, &mut sum);
+    sum.score
+}
+
+fn add_pawn_cover<A: Accumulator>(board: &Board, king: Square, color: Color, acc: &mut A) {
+    let king_file = king % 8;
+    let king_rank = relative_rank(king, color);
+
+    for file in king_file.saturating_sub(1)..=(king_file + 1).min(7) {
+        let own = nearest_pawn_ahead(board, file, king_rank, color, color);
+        let enemy = nearest_pawn_ahead(board, file, king_rank, color, color.opposite());
+
+        match own {
+            Some(distance @ 1..=2) => acc.add(idx::SHIELD + distance, 1),
+            _ => acc.add(idx::SHIELD_MISSING, 1),
+        }
+        if let Some(distance) = enemy {
+            acc.add(idx::STORM + distance, 1);
+        }
+
+        if !has_pawn_on_file(board, file, color) {
+            if has_pawn_on_file(board, file, color.opposite()) {
+                acc.add(idx::SEMI_OPEN_FILE, 1);
+            } else {
+                acc.add(idx::OPEN_FILE, 1);
+            }
+        }
+    }
+}
+
+// Unidades de ataque de las piezas rivales sobre la zona del rey
+pub fn attack_units(board: &Board, king: Square, color: Color) -> (u32, i32) {
+    let zone = king_zone(king, color);
+    let mut attackers = 0;
+    let mut units = 0;
+
+    for (square, piece) in board.squares.iter().enumerate() {
+        let Some(piece) = piece else {
+            continue;
+        };
+        let weight = ATTACK_WEIGHTS[piece.piece_type as usize];
+        if piece.color == color || weight == 0 {
+            continue;
+        }
+        let hits = board.piece_attacks(square) & zone;
+        if hits != 0 {
+            attackers += 1;
+            units += weight * hits.count_ones() as i32;
+        }
+    }
+    (attackers, units)
+}
+
+// Zona del rey: su casilla, las vecinas y una fila más hacia el rival
+pub fn king_zone(king: Square, color: Color) -> u64 {
+    let (rank, file) = (king / 8, king % 8);
+    let (low, high) = match color {
+        Color::White => (rank as isize - 1, rank as isize + 2),
+        Color::Black => (rank as isize - 2, rank as isize + 1),
+    };
+
+    let mut zone = 0;
+    for r in low.max(0)..=high.min(7) {
+        for f in file.saturating_sub(1)..=(file + 1).min(7) {
+            zone |= 1 << (r as usize * 8 + f);
+        }
+    }
+    zone
+}
+
+// Filas que separan al rey del peón de `owner` más cercano por delante en la columna
+fn nearest_pawn_ahead(
+    board: &Board,
+    file: usize,
+    king_rank: usize,
+    color: Color,
+    owner: Color,
+) -> Option<usize> {
+    (king_rank + 1..8).find_map(|rank| {
+        let square = match color {
+            Color::White => rank * 8 + file,
+            Color::Black => (7 - rank) * 8 + file,
+        };
+        board.squares[square]
+            .filter(|piece| piece.piece_type == PieceType::Pawn && piece.color == owner)
+            .map(|_| rank - king_rank)
+    })
+}
+
+fn has_pawn_on_file(board: &Board, file: usize, color: Color) -> bool {
+    (0..8).any(|rank| {
+        board.squares[rank * 8 + file]
+            .is_some_and(|piece| piece.piece_type == PieceType::Pawn && piece.color == color)
+    })
+}
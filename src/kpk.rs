@@ -0,0 +1,218 @@
+use crate::types::{Board, Color, PieceType, Square, distance};
+use std::sync::OnceLock;
+
+// Bitbase de rey y peón contra rey: para cada posición, si el bando del peón
+// gana o no. Se genera por análisis retrógrado la primera vez que se usa.
+//
+// Las posiciones se normalizan con el peón blanco en las columnas a-d (el
+// resto es simétrico), así que el índice es: rey blanco (6 bits), rey negro
+// (6 bits), bando que mueve (1 bit), columna del peón (2 bits) y fila del peón
+// de la 2 a la 7 (6 valores).
+const MAX_INDEX: usize = 2 * 24 * 64 * 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KpkResult {
+    Win,
+    Draw,
+}
+
+// Estado de cada posición durante la generación
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Status {
+    Invalid,
+    Unknown,
+    Draw,
+    Win,
+}
+
+fn index(turn: Color, white_king: Square, black_king: Square, pawn: Square) -> usize {
+    white_king | black_king << 6 | (turn as usize) << 12 | (pawn % 8) << 13 | (6 - pawn / 8) << 15
+}
+
+fn king_moves(square: Square) -> impl Iterator<Item = Square> {
+    (0..64).filter(move |&to| distance(square, to) == 1)
+}
+
+fn pawn_attacks(pawn: Square, square: Square) -> bool {
+    square / 8 == pawn / 8 + 1 && (square % 8).abs_diff(pawn % 8) == 1
+}
+
+// Clasificación inicial: posiciones imposibles y las que se resuelven sin
+// mirar más allá (coronación segura, ahogado o peón capturado)
+fn initial_status(turn: Color, white_king: Square, black_king: Square, pawn: Square) -> Status {
+    if distance(white_king, black_king) <= 1
+        || white_king == pawn
+        || black_king == pawn
+        || (turn == Color::White && pawn_attacks(pawn, black_king))
+    {
+        return Status::Invalid;
+    }
+
+    match turn {
+        Color::White => {
+            let queening = pawn + 8;
+            if pawn / 8 == 6
+                && white_king != queening
+                && (distance(black_king, queening) > 1 || distance(white_king, queening) == 1)
+            {
+                return Status::Win;
+            }
+        }
+        Color::Black => {
+            // Comerse el peón solo es legal si el rey blanco no lo defiende
+            let safe = |to: Square| distance(to, white_king) > 1 && !pawn_attacks(pawn, to);
+            let stalemate = king_moves(black_king).all(|to| !safe(to));
+            let captures = distance(black_king, pawn) == 1 && distance(white_king, pawn) > 1;
+            if stalemate || captures {
+                return Status::Draw;
+            }
+        }
+    }
+    Status::Unknown
+}
+
+// Una pasada de la clasificación: las blancas ganan si alguna jugada gana,
+// las negras hacen tablas si alguna jugada hace tablas
+fn classify(
+    db: &[Status],
+    turn: Color,
+    white_king: Square,
+    black_king: Square,
+    pawn: Square,
+) -> Status {
+    let (mut win, mut draw, mut unknown) = (false, false, false);
+    let mut visit = |status: Status| match status {
+        Status::Win => win = true,
+        Status::Draw => draw = true,
+        Status::Unknown => unknown = true,
+        Status::Invalid => {}
+    };
+
+    match turn {
+        Color::White => {
+            for to in king_moves(white_king) {
+                visit(db[index(Color::Black, to, black_king, pawn)]);
+            }
+            // La coronación ya está resuelta en la clasificación inicial
+            if pawn / 8 < 6 {
+                let push = pawn + 8;
+                visit(db[index(Color::Black, white_king, black_king, push)]);
+                if pawn / 8 == 1 && push != white_king && push != black_king {
+                    visit(db[index(Color::Black, white_king, black_king, push + 8)]);
+                }
+            }
+            if win {
+                Status::Win
+            } else if unknown {
+                Status::Unknown
+            } else {
+                Status::Draw
+            }
+        }
+        Color::Black => {
+            for to in king_moves(black_king) {
+                visit(db[index(Color::White, white_king, to, pawn)]);
+            }
+            if draw {
+                Status::Draw
+            } else if unknown {
+                Status::Unknown
+            } else {
+                Status::Win
+            }
+        }
+    }
+}
+
+// Las posiciones de todos los índices: (bando, rey blanco, rey negro, peón)
+fn positions() -> impl Iterator<Item = (Color, Square, Square, Square)> {
+    [Color::White, Color::Black].into_iter().flat_map(|turn| {
+        (8..56).filter(|pawn| pawn % 8 < 4).flat_map(move |pawn| {
+            (0..64).flat_map(move |white_king| {
+                (0..64).map(move |black_king| (turn, white_king, black_king, pawn))
+            })
+        })
+    })
+}
+
+// Genera la bitbase: un bit por posición, a 1 si las blancas ganan
+pub fn generate() -> Vec<u64> {
+    let mut db = vec![Status::Invalid; MAX_INDEX];
+    for (turn, white_king, black_king, pawn) in positions() {
+        db[index(turn, white_king, black_king, pawn)] =
+            initial_status(turn, white_king, black_king, pawn);
+    }
+
+    // Se repite hasta que ninguna posición cambia: lo que quede sin resolver
+    // es que las negras pueden aguantar para siempre
+    let mut changed = true;
+    while changed {
+        changed = false;
+        for (turn, white_king, black_king, pawn) in positions() {
+            let i = index(turn, white_king, black_king, pawn);
+            if db[i] == Status::Unknown {
+                let status = classify(&db, turn, white_king, black_king, pawn);
+                if status != Status::Unknown {
+                    db[i] = status;
+                    changed = true;
+                }
+            }
+        }
+    }
+
+    let mut bits = vec![0u64; MAX_INDEX / 64];
+    for (i, status) in db.iter().enumerate() {
+        if *status == Status::Win {
+            bits[i / 64] |= 1 << (i % 64);
+        }
+    }
+    bits
+}
+
+fn bitbase() -> &'static [u64] {
+    static BITBASE: OnceLock<Vec<u64>> = OnceLock::new();
+    BITBASE.get_or_init(generate)
+}
+
+// Genera la bitbase por adelantado para no pagarla en mitad de una búsqueda
+pub fn init() {
+    bitbase();
+}
+
+// Resultado para el bando del peón si la posición es de rey y peón contra rey
+pub fn probe_kpk(board: &Board) -> Option<KpkResult> {
+    let mut kings = [None; 2];
+    let mut pawn = None;
+    for (square, piece) in board.squares.iter().enumerate() {
+        let Some(piece) = piece else { continue };
+        match piece.piece_type {
+            PieceType::King => kings[piece.color as usize] = Some(square),
+            PieceType::Pawn if pawn.is_none() => pawn = Some((square, piece.color)),
+            _ => return None,
+        }
+    }
+    let (mut pawn, strong) = pawn?;
+    let (mut strong_king, mut weak_king) =
+        (kings[strong as usize]?, kings[strong.opposite() as usize]?);
+
+    // El bando del peón pasa a ser las blancas, con el peón en las columnas a-d
+    let mut turn = board.turn;
+    if strong == Color::Black {
+        pawn ^= 56;
+        strong_king ^= 56;
+        weak_king ^= 56;
+        turn = turn.opposite();
+    }
+    if pawn % 8 >= 4 {
+        pawn ^= 7;
+        strong_king ^= 7;
+        weak_king ^= 7;
+    }
+
+    let i = index(turn, strong_king, weak_king, pawn);
+    if bitbase()[i / 64] >> (i % 64) & 1 == 1 {
+        Some(KpkResult::Win)
+    } else {
+        Some(KpkResult::Draw)
+    }
+}
@@ -1,21 +1,116 @@
 #[allow(dead_code)]
+mod bench;
+#[allow(dead_code)]
 mod board;
 #[allow(dead_code)]
+mod endgame;
+#[allow(dead_code)]
 mod evaluation;
 #[allow(dead_code)]
+mod king_safety;
+#[allow(dead_code)]
+mod kpk;
+#[allow(dead_code)]
+mod mate;
+#[allow(dead_code)]
+mod mobility;
+#[allow(dead_code)]
+mod movepick;
+#[allow(dead_code)]
+mod nnue;
+#[allow(dead_code)]
+mod options;
+#[allow(dead_code)]
+mod params;
+#[allow(dead_code)]
+mod pawns;
+#[allow(dead_code)]
+mod psqt;
+#[allow(dead_code)]
 mod search;
 #[allow(dead_code)]
+mod see;
+#[allow(dead_code)]
+mod skill;
+#[allow(dead_code)]
+mod smp;
+#[allow(dead_code)]
+mod tablebase;
+#[allow(dead_code)]
+mod timeman;
+#[allow(dead_code)]
+mod tt;
+#[allow(dead_code)]
+mod tune;
+#[allow(dead_code)]
 mod types;
+#[allow(dead_code)]
+mod zobrist;
 
-use crate::{search::search_best_move, types::Board};
+use crate::{
+    bench::{DEFAULT_BENCH_DEPTH, DEFAULT_BENCH_HASH, DEFAULT_BENCH_THREADS, bench},
+    evaluation::trace,
+    mate::MateSolver,
+    nnue::Network,
+    options::{
+        CLEAR_HASH, EVAL_FILE, EngineOptions, HASH, MOVE_OVERHEAD, MULTI_PV, SKILL_LEVEL,
+        SKILL_SEED, TABLEBASE_PATH, THREADS, UCI_CHESS960, UCI_ELO, UCI_LIMIT_STRENGTH, USE_NNUE,
+        parse_setoption,
+    },
+    search::{PvLine, SearchConfig, SearchShared},
+    skill::{Rng, SKILL_MULTI_PV, Skill},
+    smp::{THREAD_STACK_SIZE, search_parallel},
+    tablebase::{MAX_MEN, Tablebases, run_generate},
+    timeman::SearchLimits,
+    tt::TranspositionTable,
+    tune::{DEFAULT_EPOCHS, DEFAULT_OUTPUT, Method, run_tune},
+    types::{Board, Move},
+};
 use std::{
+    env,
     fs::OpenOptions,
     io::{self, BufRead, Write},
+    sync::Arc,
+    thread::{self, JoinHandle},
+    time::{SystemTime, UNIX_EPOCH},
 };
 
+// Profundidad de "go" cuando la GUI no manda ni reloj ni profundidad
+const DEFAULT_DEPTH: u32 = 6;
+
 fn main() {
+    // "chess-bot-gm bench [depth] [threads] [hash]" desde la línea de comandos
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().is_some_and(|arg| arg == "bench") {
+        let args: Vec<&str> = args[1..].iter().map(String::as_str).collect();
+        run_bench(&args);
+        return;
+    }
+    // "chess-bot-gm tune <posiciones> [adam|local] [épocas] [salida]"
+    if args.first().is_some_and(|arg| arg == "tune") {
+        let args: Vec<&str> = args[1..].iter().map(String::as_str).collect();
+        run_tune_command(&args);
+        return;
+    }
+    // "chess-bot-gm tablebase <directorio> [firmas...]"
+    if args.first().is_some_and(|arg| arg == "tablebase") {
+        let args: Vec<&str> = args[1..].iter().map(String::as_str).collect();
+        run_tablebase_command(&args);
+        return;
+    }
+
+    // La bitbase de rey y peón se genera antes de la primera búsqueda
+    kpk::init();
+
     let stdin = io::stdin();
     let mut board = Board::initial_position();
+    let mut options = EngineOptions::new();
+    let mut tt = Arc::new(TranspositionTable::new(options.spin(HASH) as usize));
+    let mut search: Option<RunningSearch> = None;
+    // Red cargada desde EvalFile; solo se usa con "Use NNUE" activado
+    let mut network: Option<Arc<Network>> = None;
+    // Tablas de finales cargadas desde TablebasePath
+    let mut tablebases: Option<Arc<Tablebases>> = None;
 
     for line in stdin.lock().lines() {
         let input = line.unwrap();
@@ -30,13 +125,18 @@ fn main() {
             "uci" => {
                 println!("id name ChessBot621");
                 println!("id author Vicente Garcia Marti");
+                for option in options.iter() {
+                    println!("{}", option);
+                }
                 println!("uciok");
             }
             "isready" => {
                 println!("readyok");
             }
             "ucinewgame" => {
+                stop_search(&mut search);
                 board = Board::initial_position();
+                tt_mut(&mut tt).clear();
             }
             "position" => {
                 if parts.len() < 2 {
@@ -98,31 +198,340 @@ fn main() {
                 }
             }
             "go" => {
-                let best_move = search_best_move(&board, 4); // Profundidad de búsqueda fija
-
-                let best_move_str = match best_move {
-                    Some(mv) => mv.to_string(),
-                    None => "0000".to_string(), // Movimiento nulo si no se encuentra ninguno
-                };
-
-                println!("bestmove {}", best_move_str);
+                wait_search(&mut search);
+                let mut limits = SearchLimits::parse(&parts[1..]);
+                limits.move_overhead = options.spin(MOVE_OVERHEAD) as u64;
+                let nnue = active_network(&options, &network);
+                search = Some(start_search(
+                    &board,
+                    limits,
+                    &options,
+                    &tt,
+                    nnue,
+                    tablebases.clone(),
+                ));
+            }
+            "stop" => {
+                stop_search(&mut search);
+            }
+            "ponderhit" => {
+                // El rival jugó lo esperado: seguimos con el reloj normal, contando
+                // como ya gastado el tiempo que llevamos pensando
+                if let Some(search) = &search {
+                    search.shared.set_pondering(false);
+                }
+            }
+            "eval" => {
+                // Desglose de la evaluación estática de la posición actual
+                println!("{}", trace(&board));
+                if let Some(net) = active_network(&options, &network) {
+                    println!("NNUE (bando que mueve): {} cp", net.evaluate(&board));
+                }
+            }
+            "bench" => {
+                stop_search(&mut search);
+                run_bench(&parts[1..]);
             }
             "quit" => {
                 break;
             }
             "setoption" => {
+                stop_search(&mut search);
                 // Lichess manda configuraciones (como el Move Overhead).
-                // En el futuro, se parsea: name <x> value <y>
+                // Formato: setoption name <x> [value <y>]
+                let result = parse_setoption(&parts[1..])
+                    .and_then(|(name, value)| options.set(&name, value.as_deref()));
+
+                match result {
+                    Ok(name) => apply_option(
+                        name,
+                        &mut options,
+                        tt_mut(&mut tt),
+                        &mut network,
+                        &mut tablebases,
+                    ),
+                    Err(e) => println!("info string {}", e),
+                }
             }
             _ => {}
         }
     }
+
+    stop_search(&mut search);
+}
+
+// Búsqueda en curso. Corre en su propio hilo para que el bucle de UCI pueda
+// seguir atendiendo "stop", "ponderhit" o "isready" mientras tanto.
+struct RunningSearch {
+    shared: Arc<SearchShared>,
+    handle: JoinHandle<()>,
+}
+
+fn start_search(
+    board: &Board,
+    mut limits: SearchLimits,
+    options: &EngineOptions,
+    tt: &Arc<TranspositionTable>,
+    nnue: Option<Arc<Network>>,
+    tablebases: Option<Arc<Tablebases>>,
+) -> RunningSearch {
+    if let Some(moves) = limits.mate {
+        return start_mate_search(board, moves);
+    }
+
+    let skill = skill_from_options(options);
+    skill.limit(&mut limits);
+    if !limits.is_timed() && !limits.infinite && limits.depth.is_none() && limits.nodes.is_none() {
+        limits.depth = Some(DEFAULT_DEPTH); // Sin reloj: profundidad fija
+    }
+
+    let mut multi_pv = options.spin(MULTI_PV) as usize;
+    if skill.enabled() {
+        multi_pv = multi_pv.max(SKILL_MULTI_PV); // Candidatas para elegir
+    }
+    let config = SearchConfig {
+        multi_pv,
+        print_info: true,
+        nnue,
+        tablebases,
+        ..SearchConfig::default()
+    };
+    let threads = options.spin(THREADS) as usize;
+    let mut rng = Rng::new(skill_seed(options));
+
+    let shared = Arc::new(SearchShared::default());
+    shared.set_pondering(limits.ponder || limits.infinite);
+
+    let board = board.clone();
+    let tt = tt.clone();
+    let search_shared = shared.clone();
+    let handle = thread::Builder::new()
+        .stack_size(THREAD_STACK_SIZE)
+        .spawn(move || {
+            let result = search_parallel(&board, &limits, &tt, threads, config, search_shared);
+            let best_move = skill
+                .pick_move(&result.lines, &mut rng)
+                .or(result.best_move);
+            println!("{}", bestmove_command(best_move, &result.lines));
+        })
+        .expect("No se pudo crear el hilo de búsqueda");
+
+    RunningSearch { shared, handle }
+}
+
+// Espera a que termine la búsqueda en curso, si la hay
+fn wait_search(search: &mut Option<RunningSearch>) {
+    if let Some(search) = search.take() {
+        search.handle.join().expect("El hilo de búsqueda falló");
+    }
+}
+
+// Para la búsqueda en curso y espera a su "bestmove". Es lo que hay que usar
+// antes de tocar la tabla o las opciones: con "go infinite" o en ponder la
+// búsqueda no terminaría nunca por sí sola.
+fn stop_search(search: &mut Option<RunningSearch>) {
+    if let Some(search) = search {
+        search.shared.stop();
+    }
+    wait_search(search);
+}
+
+// La tabla solo se comparte durante una búsqueda; fuera de ella es nuestra
+fn tt_mut(tt: &mut Arc<TranspositionTable>) -> &mut TranspositionTable {
+    Arc::get_mut(tt).expect("La tabla de transposición sigue en uso")
+}
+
+// "bestmove X ponder Y": la jugada que esperamos del rival es la siguiente de
+// la variante de la jugada elegida
+fn bestmove_command(best_move: Option<Move>, lines: &[PvLine]) -> String {
+    let Some(best_move) = best_move else {
+        return "bestmove 0000".to_string(); // Movimiento nulo si no se encuentra ninguno
+    };
+    let ponder_move = lines
+        .iter()
+        .find(|line| line.pv.first() == Some(&best_move))
+        .and_then(|line| line.pv.get(1));
+
+    match ponder_move {
+        Some(ponder_move) => format!("bestmove {} ponder {}", best_move, ponder_move),
+        None => format!("bestmove {}", best_move),
+    }
+}
+
+// UCI_LimitStrength manda sobre el Skill Level
+fn skill_from_options(options: &EngineOptions) -> Skill {
+    if options.check(UCI_LIMIT_STRENGTH) {
+        Skill::from_elo(options.spin(UCI_ELO) as u32)
+    } else {
+        Skill::from_level(options.spin(SKILL_LEVEL) as u32)
+    }
+}
+
+fn skill_seed(options: &EngineOptions) -> u64 {
+    match options.spin(SKILL_SEED) {
+        0 => SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(1, |time| time.as_nanos() as u64),
+        seed => seed as u64,
+    }
+}
+
+// Argumentos opcionales: [depth] [threads] [hash]
+fn run_bench(args: &[&str]) {
+    let arg = |i: usize| args.get(i).and_then(|arg| arg.parse::<usize>().ok());
+    let depth = arg(0).map_or(DEFAULT_BENCH_DEPTH, |depth| depth as u32);
+    let threads = arg(1).unwrap_or(DEFAULT_BENCH_THREADS);
+    let hash = arg(2).unwrap_or(DEFAULT_BENCH_HASH);
+    bench(depth, threads, hash);
+}
+
+// Argumentos: <posiciones> [adam|local] [épocas] [salida]
+fn run_tune_command(args: &[&str]) {
+    let Some(path) = args.first() else {
+        eprintln!("Uso: chess-bot-gm tune <posiciones> [adam|local] [épocas] [salida]");
+        return;
+    };
+    let method = match args.get(1) {
+        Some(&"local") => Method::LocalSearch,
+        _ => Method::Adam,
+    };
+    let epochs = args
+        .get(2)
+        .and_then(|arg| arg.parse().ok())
+        .unwrap_or(DEFAULT_EPOCHS);
+    let output = args.get(3).copied().unwrap_or(DEFAULT_OUTPUT);
+    if let Err(e) = run_tune(path, method, epochs, output) {
+        eprintln!("{}", e);
+    }
+}
+
+// Argumentos: <directorio> [firmas...]
+fn run_tablebase_command(args: &[&str]) {
+    let Some(dir) = args.first() else {
+        eprintln!("Uso: chess-bot-gm tablebase <directorio> [firmas...]");
+        return;
+    };
+    if let Err(e) = run_generate(dir, &args[1..]) {
+        eprintln!("{}", e);
+    }
+}
+
+// "go mate N": solo buscamos un mate forzado, sin evaluar. También corre en
+// su propio hilo y se puede cortar con "stop".
+fn start_mate_search(board: &Board, moves: u32) -> RunningSearch {
+    let shared = Arc::new(SearchShared::default());
+    let mut solver = MateSolver::new();
+    solver.shared = shared.clone();
+
+    let board = board.clone();
+    let handle = thread::Builder::new()
+        .stack_size(THREAD_STACK_SIZE)
+        .spawn(move || go_mate(&board, moves, &mut solver))
+        .expect("No se pudo crear el hilo de búsqueda");
+
+    RunningSearch { shared, handle }
+}
+
+fn go_mate(board: &Board, moves: u32, solver: &mut MateSolver) {
+    match solver.solve(board, moves) {
+        Some(line) => {
+            let pv: Vec<String> = line.iter().map(|mv| mv.to_string()).collect();
+            println!(
+                "info depth {} score mate {} nodes {} pv {}",
+                line.len(),
+                line.len().div_ceil(2),
+                solver.nodes,
+                pv.join(" ")
+            );
+            println!("bestmove {}", line[0]);
+        }
+        None if solver.stopped => {
+            println!("info string Búsqueda de mate interrumpida");
+            println!("bestmove 0000");
+        }
+        None => {
+            println!("info string No hay mate en {} jugadas", moves);
+            println!("bestmove 0000");
+        }
+    }
+}
+
+// Aplica los efectos de una opción recién cambiada. El resto de opciones
+// se leen del registro en el momento en que se necesitan.
+fn apply_option(
+    name: &str,
+    options: &mut EngineOptions,
+    tt: &mut TranspositionTable,
+    network: &mut Option<Arc<Network>>,
+    tablebases: &mut Option<Arc<Tablebases>>,
+) {
+    match name {
+        HASH => tt.resize(options.spin(HASH) as usize),
+        CLEAR_HASH => tt.clear(),
+        EVAL_FILE => *network = load_network(options.text(EVAL_FILE)),
+        TABLEBASE_PATH => *tablebases = load_tablebases(options.text(TABLEBASE_PATH)),
+        UCI_CHESS960 if options.check(UCI_CHESS960) => {
+            println!("info string UCI_Chess960 no está soportado todavía");
+            let _ = options.set(UCI_CHESS960, Some("false"));
+        }
+        _ => {}
+    }
+}
+
+fn load_network(path: &str) -> Option<Arc<Network>> {
+    if path.is_empty() || path == "<empty>" {
+        return None;
+    }
+    match Network::load(path) {
+        Ok(net) => {
+            println!(
+                "info string Red NNUE cargada: {} ({} neuronas ocultas)",
+                path, net.hidden
+            );
+            Some(Arc::new(net))
+        }
+        Err(e) => {
+            println!("info string {}", e);
+            None
+        }
+    }
+}
+
+fn load_tablebases(dir: &str) -> Option<Arc<Tablebases>> {
+    if dir.is_empty() || dir == "<empty>" {
+        return None;
+    }
+    match Tablebases::load(dir) {
+        Ok(tablebases) => {
+            println!(
+                "info string Tablas de finales cargadas: {} ({} tablas de hasta {} piezas)",
+                dir,
+                tablebases.len(),
+                MAX_MEN
+            );
+            Some(Arc::new(tablebases))
+        }
+        Err(e) => {
+            println!("info string {}", e);
+            None
+        }
+    }
+}
+
+// La red que usa la búsqueda: la cargada, si "Use NNUE" está activado
+fn active_network(options: &EngineOptions, network: &Option<Arc<Network>>) -> Option<Arc<Network>> {
+    if !options.check(USE_NNUE) {
+        return None;
+    }
+    if network.is_none() {
+        println!("info string Use NNUE sin EvalFile cargado: evaluación clásica");
+    }
+    network.clone()
 }
 
 fn log_to_file(msg: &str) {
     let mut file = OpenOptions::new()
         .create(true)
-        .write(true)
         .append(true)
         .open("debug.log")
         .unwrap(); // Si falla el log, que explote todo (para avisarte)
@@ -134,7 +543,24 @@ fn log_to_file(msg: &str) {
 
 #[cfg(test)]
 mod tests {
+    mod bench_tests;
     mod board_tests;
+    mod endgame_tests;
+    mod king_safety_tests;
+    mod kpk_tests;
+    mod mate_tests;
+    mod mobility_tests;
+    mod movepick_tests;
+    mod nnue_tests;
+    mod options_tests;
+    mod pawns_tests;
     mod search_tests;
+    mod see_tests;
+    mod skill_tests;
+    mod smp_tests;
+    mod tablebase_tests;
+    mod timeman_tests;
+    mod tt_tests;
+    mod tune_tests;
     mod types_tests;
 }
@@ -0,0 +1,139 @@
+use crate::{
+    search::SearchShared,
+    types::{Board, Move},
+};
+use std::{collections::HashMap, sync::Arc};
+
+// Cada cuántos nodos se mira si han mandado "stop"
+const STOP_CHECK_INTERVAL: u64 = 1024;
+
+// Buscador de mates forzados para "go mate N". No evalúa nada: el bando que
+// ataca solo prueba jugadas de jaque y el que defiende prueba todas sus
+// respuestas. Se profundiza de 1 a N jugadas, así el mate que se encuentra es
+// siempre el más corto.
+pub struct MateSolver {
+    // Si es false el atacante prueba también jugadas tranquilas (mucho más lento)
+    pub checks_only: bool,
+    pub nodes: u64,
+    // Compartido con el bucle de UCI para poder cortar la búsqueda con "stop"
+    pub shared: Arc<SearchShared>,
+    // Se activa al recibir "stop": a partir de ahí nada de lo que se devuelva vale
+    pub stopped: bool,
+    // Posiciones del atacante ya refutadas: no hay mate en este número de jugadas
+    refuted: HashMap<u64, u32>,
+}
+
+impl Default for MateSolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MateSolver {
+    pub fn new() -> Self {
+        MateSolver {
+            checks_only: true,
+            nodes: 0,
+            shared: Arc::default(),
+            stopped: false,
+            refuted: HashMap::new(),
+        }
+    }
+
+    // Devuelve la variante del mate más corto en como mucho `max_moves`
+    // jugadas del bando que mueve, o None si no lo hay o si se para antes.
+    pub fn solve(&mut self, board: &Board, max_moves: u32) -> Option<Vec<Move>> {
+        self.nodes = 0;
+        self.stopped = false;
+        self.refuted.clear();
+        for moves in 1..=max_moves {
+            let line = self.attack(board, moves);
+            if self.stopped {
+                return None;
+            }
+            if line.is_some() {
+                return line;
+            }
+        }
+        None
+    }
+
+    // Cuenta el nodo y comprueba de vez en cuando si hay que parar
+    fn visit(&mut self) -> bool {
+        self.nodes += 1;
+        if self.nodes.is_multiple_of(STOP_CHECK_INTERVAL) && self.shared.is_stopped() {
+            self.stopped = true;
+        }
+        !self.stopped
+    }
+
+    fn attack(&mut self, board: &Board, moves_left: u32) -> Option<Vec<Move>> {
+        if !self.visit() {
+            return None;
+        }
+        if self
+            .refuted
+            .get(&board.hash)
+            .is_some_and(|&refuted| refuted >= moves_left)
+        {
+            return None;
+        }
+
+        for mv in board.generate_moves() {
+            let mut new_board = board.clone();
+            new_board.make_move(&mv);
+            let gives_check = new_board.is_king_attacked(new_board.turn);
+            // En la última jugada solo un jaque puede ser mate
+            if !gives_check && (self.checks_only || moves_left == 1) {
+                continue;
+            }
+
+            if let Some(mut line) = self.defend(&new_board, moves_left) {
+                line.insert(0, mv);
+                return Some(line);
+            }
+            // Si se ha parado, no se ha probado todo: no está refutada
+            if self.stopped {
+                return None;
+            }
+        }
+
+        self.refuted.insert(board.hash, moves_left);
+        None
+    }
+
+    // Hay mate si todas las respuestas pierden. La variante sigue la defensa
+    // que más aguanta.
+    fn defend(&mut self, board: &Board, moves_left: u32) -> Option<Vec<Move>> {
+        if !self.visit() {
+            return None;
+        }
+        let replies = board.generate_moves();
+        if replies.is_empty() {
+            // Mate si está en jaque; el ahogado no cuenta
+            return board.is_king_attacked(board.turn).then(Vec::new);
+        }
+        if moves_left == 1 {
+            return None;
+        }
+
+        let mut longest: Option<Vec<Move>> = None;
+        for reply in replies {
+            let mut new_board = board.clone();
+            new_board.make_move(&reply);
+            let mut line = self.attack(&new_board, moves_left - 1)?;
+            line.insert(0, reply);
+            if longest
+                .as_ref()
+                .is_none_or(|longest| line.len() > longest.len())
+            {
+                longest = Some(line);
+            }
+        }
+        longest
+    }
+}
+
+pub fn find_mate(board: &Board, max_moves: u32) -> Option<Vec<Move>> {
+    MateSolver::new().solve(board, max_moves)
+}
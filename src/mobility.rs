@@ -0,0 +1,173 @@
+use crate::{
+    evaluation::Score,
+    params::{Accumulator, PARAMS, ScoreSum, Side, idx},
+    types::{Board, Color, PieceType, Square, relative_rank},
+};
+
+// Casillas útiles que se consideran normales para cada pieza (caballo, alfil,
+// torre, dama): cada una por encima suma el peso de MOBILITY y cada una por
+// debajo lo resta
+pub const MOBILITY_BASE: [i32; 6] = [0, 4, 6, 7, 13, 0];
+
+// Lo que necesitan todos los términos de un bando: sus casillas ocupadas,
+// las que atacan los peones rivales y las columnas con peones de cada bando
+struct SideInfo {
+    own_pieces: u64,
+    enemy_pawn_attacks: u64,
+    own_pawn_files: u8,
+    enemy_pawn_files: u8,
+}
+
+fn side_info(board: &Board, color: Color) -> SideInfo {
+    let mut info = SideInfo {
+        own_pieces: 0,
+        enemy_pawn_attacks: 0,
+        own_pawn_files: 0,
+        enemy_pawn_files: 0,
+    };
+    for (square, piece) in board.squares.iter().enumerate() {
+        let Some(piece) = piece else {
+            continue;
+        };
+        let is_pawn = piece.piece_type == PieceType::Pawn;
+        if piece.color == color {
+            info.own_pieces |= 1 << square;
+            if is_pawn {
+                info.own_pawn_files |= 1 << (square % 8);
+            }
+        } else if is_pawn {
+            info.enemy_pawn_attacks |= board.piece_attacks(square);
+            info.enemy_pawn_files |= 1 << (square % 8);
+        }
+    }
+    info
+}
+
+// Casillas a las que puede ir una pieza sin caer en una propia ni en una
+// controlada por peones rivales
+pub fn mobility(board: &Board, square: Square) -> u32 {
+    let Some(piece) = board.squares[square] else {
+        return 0;
+    };
+    let info = side_info(board, piece.color);
+    safe_squares(board, square, &info)
+}
+
+fn safe_squares(board: &Board, square: Square, info: &SideInfo) -> u32 {
+    (board.piece_attacks(square) & !info.own_pieces & !info.enemy_pawn_attacks).count_ones()
+}
+
+// Movilidad y actividad de las piezas desde el punto de vista de las blancas
+pub fn piece_activity(board: &Board) -> Score {
+    let mut sum = ScoreSum::new(&PARAMS);
+    side_activity(board, Color::White, &mut sum);
+    side_activity(board, Color::Black, &mut Side::new(&mut sum, -1));
+    sum.score
+}
+
+pub fn side_activity<A: Accumulator>(board: &Board, color: Color, acc: &mut A) {
+    let info = side_info(board, color);
+    let mut bishops = 0;
+
+    for (square, piece) in board.squares.iter().enumerate() {
+        let Some(piece) = piece else {
+            continue;
+        };
+        if piece.color != color {
+            continue;
+        }
+        let piece_type = piece.piece_type as usize;
+        if MOBILITY_BASE[piece_type] > 0 {
+            let squares = safe_squares(board, square, &info) as i32;
+            acc.add(
+                idx::MOBILITY + piece_type,
+                squares - MOBILITY_BASE[piece_type],
+            );
+        }
+
+        match piece.piece_type {
+            PieceType::Rook => {
+                let file = 1 << (square % 8);
+                if (info.own_pawn_files | info.enemy_pawn_files) & file == 0 {
+                    acc.add(idx::ROOK_OPEN_FILE, 1);
+                } else if info.own_pawn_files & file == 0 {
+                    acc.add(idx::ROOK_SEMI_OPEN_FILE, 1);
+                }
+                if is_rook_on_seventh(board, square, color) {
+                    acc.add(idx::ROOK_SEVENTH, 1);
+                }
+            }
+            PieceType::Knight if is_outpost(board, square, color) => {
+                acc.add(idx::KNIGHT_OUTPOST, 1);
+            }
+            PieceType::Bishop => {
+                bishops += 1;
+                let pawns = same_color_pawns(board, square, color) as i32;
+                acc.add(idx::BAD_BISHOP_PAWN, pawns);
+            }
+            _ => {}
+        }
+    }
+
+    if bishops >= 2 {
+        acc.add(idx::BISHOP_PAIR, 1);
+    }
+}
+
+fn is_pawn(board: &Board, square: Square, color: Color) -> bool {
+    board.squares[square]
+        .is_some_and(|piece| piece.piece_type == PieceType::Pawn && piece.color == color)
+}
+
+// La torre en séptima pesa si encierra al rey en la octava o ataca peones
+pub fn is_rook_on_seventh(board: &Board, square: Square, color: Color) -> bool {
+    if relative_rank(square, color) != 6 {
+        return false;
+    }
+    let enemy = color.opposite();
+    let king_on_eighth = board
+        .find_king(enemy)
+        .is_some_and(|king| relative_rank(king, color) == 7);
+    let seventh = square / 8 * 8;
+    king_on_eighth || (seventh..seventh + 8).any(|square| is_pawn(board, square, enemy))
+}
+
+// Caballo en la 4ª a 6ª fila, defendido por un peón y al que ningún peón
+// rival puede echar
+pub fn is_outpost(board: &Board, square: Square, color: Color) -> bool {
+    let rank = relative_rank(square, color);
+    if !(3..=5).contains(&rank) {
+        return false;
+    }
+
+    let (rank, file) = (square / 8, square % 8);
+    let files = file.saturating_sub(1)..=(file + 1).min(7);
+    let behind = match color {
+        Color::White => rank - 1,
+        Color::Black => rank + 1,
+    };
+    let defended = files
+        .clone()
+        .any(|f| f != file && is_pawn(board, behind * 8 + f, color));
+
+    let ranks_ahead: Vec<usize> = match color {
+        Color::White => (rank + 1..8).collect(),
+        Color::Black => (0..rank).collect(),
+    };
+    let enemy = color.opposite();
+    let attackable = files.filter(|&f| f != file).any(|f| {
+        ranks_ahead
+            .iter()
+            .any(|&r| is_pawn(board, r * 8 + f, enemy))
+    });
+
+    defended && !attackable
+}
+
+// Peones propios en casillas del mismo color que el alfil: le quitan diagonales
+pub fn same_color_pawns(board: &Board, square: Square, color: Color) -> u32 {
+    let square_color = |square: Square| (square / 8 + square % 8) % 2;
+    (0..64)
+        .filter(|&s| square_color(s) == square_color(square) && is_pawn(board, s, color))
+        .count() as u32
+}
@@ -0,0 +1,207 @@
+use crate::{
+    search::MAX_PLY,
+    see::see,
+    types::{Board, Color, Move, PieceType},
+    zobrist::piece_index,
+};
+
+// Límite de la tabla de historia; con la "gravedad" los valores nunca lo superan
+const MAX_HISTORY: i32 = 16384;
+
+const TT_MOVE_SCORE: i32 = 1_000_000;
+const GOOD_CAPTURE_SCORE: i32 = 500_000;
+const KILLER_SCORES: [i32; 2] = [400_000, 390_000];
+const COUNTERMOVE_SCORE: i32 = 300_000;
+// Las jugadas tranquilas van entre la contrajugada y las malas capturas,
+// ordenadas por su valor en la tabla de historia (±MAX_HISTORY)
+const BAD_CAPTURE_SCORE: i32 = -500_000;
+
+// Heurísticas de ordenación que se aprenden durante la búsqueda.
+// Cada hilo de búsqueda tiene las suyas.
+pub struct MoveOrdering {
+    killers: [[Option<Move>; 2]; MAX_PLY as usize],
+    // Tabla "butterfly": [color][origen][destino]
+    history: [[[i32; 64]; 64]; 2],
+    // Respuesta que refutó la última vez a [pieza][destino] de la jugada anterior
+    countermoves: [[Option<Move>; 64]; 12],
+}
+
+impl Default for MoveOrdering {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MoveOrdering {
+    pub fn new() -> Self {
+        MoveOrdering {
+            killers: [[None; 2]; MAX_PLY as usize],
+            history: [[[0; 64]; 64]; 2],
+            countermoves: [[None; 64]; 12],
+        }
+    }
+
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    pub fn killers(&self, ply: i32) -> [Option<Move>; 2] {
+        self.killers[ply as usize]
+    }
+
+    pub fn history(&self, color: Color, mv: &Move) -> i32 {
+        self.history[color as usize][mv.from][mv.to]
+    }
+
+    pub fn countermove(&self, board: &Board, prev_move: Option<Move>) -> Option<Move> {
+        let prev_move = prev_move?;
+        // La pieza que acaba de mover el rival está ahora en su casilla de destino
+        let piece = board.squares[prev_move.to]?;
+        self.countermoves[piece_index(piece)][prev_move.to]
+    }
+
+    // Se llama cuando una jugada tranquila produce un corte beta
+    pub fn update_quiet_cutoff(
+        &mut self,
+        board: &Board,
+        mv: Move,
+        tried_quiets: &[Move],
+        depth: u32,
+        ply: i32,
+        prev_move: Option<Move>,
+    ) {
+        let killers = &mut self.killers[ply as usize];
+        if killers[0] != Some(mv) {
+            killers[1] = killers[0];
+            killers[0] = Some(mv);
+        }
+
+        let bonus = (depth * depth) as i32;
+        self.update_history(board.turn, &mv, bonus);
+        // Las jugadas tranquilas que probamos antes y no cortaron se penalizan
+        for quiet in tried_quiets.iter().filter(|quiet| **quiet != mv) {
+            self.update_history(board.turn, quiet, -bonus);
+        }
+
+        if let Some(prev_move) = prev_move
+            && let Some(piece) = board.squares[prev_move.to]
+        {
+            self.countermoves[piece_index(piece)][prev_move.to] = Some(mv);
+        }
+    }
+
+    // Actualización con "gravedad": cuanto más cerca del límite, menos se mueve el valor
+    fn update_history(&mut self, color: Color, mv: &Move, bonus: i32) {
+        let bonus = bonus.clamp(-MAX_HISTORY, MAX_HISTORY);
+        let entry = &mut self.history[color as usize][mv.from][mv.to];
+        *entry += bonus - *entry * bonus.abs() / MAX_HISTORY;
+    }
+}
+
+fn attacker_rank(piece_type: PieceType) -> i32 {
+    match piece_type {
+        PieceType::Pawn => 1,
+        PieceType::Knight => 2,
+        PieceType::Bishop => 3,
+        PieceType::Rook => 4,
+        PieceType::Queen => 5,
+        PieceType::King => 6,
+    }
+}
+
+// MVV-LVA: víctima más valiosa primero y, a igualdad, el atacante más barato
+pub fn mvv_lva(board: &Board, mv: &Move) -> i32 {
+    let victim = board.captured_piece(mv).map_or(0, attacker_rank);
+    let promotion = mv.promotion.map_or(0, attacker_rank);
+    let attacker = board.squares[mv.from].map_or(0, |piece| attacker_rank(piece.piece_type));
+    (victim + promotion) * 10 - attacker
+}
+
+// Devuelve las jugadas de mayor a menor puntuación. Como muchas veces hay
+// un corte con las primeras, se elige la mejor restante en cada paso en lugar
+// de ordenar la lista entera.
+pub struct MovePicker {
+    moves: Vec<(Move, i32)>,
+    index: usize,
+}
+
+impl MovePicker {
+    pub fn new(
+        board: &Board,
+        moves: Vec<Move>,
+        tt_move: Option<Move>,
+        ordering: &MoveOrdering,
+        ply: i32,
+        prev_move: Option<Move>,
+    ) -> Self {
+        let killers = ordering.killers(ply);
+        let countermove = ordering.countermove(board, prev_move);
+
+        let moves = moves
+            .into_iter()
+            .map(|mv| {
+                let score = if Some(mv) == tt_move {
+                    TT_MOVE_SCORE
+                } else if board.is_capture(&mv) || mv.promotion.is_some() {
+                    if see(board, &mv) >= 0 {
+                        GOOD_CAPTURE_SCORE + mvv_lva(board, &mv)
+                    } else {
+                        BAD_CAPTURE_SCORE + mvv_lva(board, &mv)
+                    }
+                } else if Some(mv) == killers[0] {
+                    KILLER_SCORES[0]
+                } else if Some(mv) == killers[1] {
+                    KILLER_SCORES[1]
+                } else if Some(mv) == countermove {
+                    COUNTERMOVE_SCORE
+                } else {
+                    ordering.history(board.turn, &mv)
+                };
+                (mv, score)
+            })
+            .collect();
+
+        MovePicker { moves, index: 0 }
+    }
+
+    // Solo capturas y promociones (búsqueda de quietud), por MVV-LVA
+    pub fn captures(board: &Board, moves: Vec<Move>) -> Self {
+        let moves = moves
+            .into_iter()
+            .map(|mv| {
+                let score = mvv_lva(board, &mv);
+                (mv, score)
+            })
+            .collect();
+        MovePicker { moves, index: 0 }
+    }
+
+    // Orden de generación, sin heurísticas (para comparar en pruebas)
+    pub fn unordered(moves: Vec<Move>) -> Self {
+        let moves = moves.into_iter().map(|mv| (mv, 0)).collect();
+        MovePicker { moves, index: 0 }
+    }
+}
+
+impl Iterator for MovePicker {
+    type Item = Move;
+
+    fn next(&mut self) -> Option<Move> {
+        if self.index >= self.moves.len() {
+            return None;
+        }
+
+        let mut best = self.index;
+        for i in self.index + 1..self.moves.len() {
+            if self.moves[i].1 > self.moves[best].1 {
+                best = i;
+            }
+        }
+        // Desplazamos en lugar de intercambiar para mantener el orden de los empates
+        self.moves[self.index..=best].rotate_right(1);
+
+        let mv = self.moves[self.index].0;
+        self.index += 1;
+        Some(mv)
+    }
+}
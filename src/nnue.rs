@@ -0,0 +1,399 @@
+use crate::{
+    skill::Rng,
+    types::{Board, Color, Move, Piece, PieceType, Square},
+};
+use std::fs;
+
+// Red 768 -> N -> 1 con dos perspectivas: cada bando ve las piezas (propias o
+// rivales, de cada tipo, en cada casilla) desde su lado del tablero. La capa
+// oculta de cada perspectiva es un acumulador que se actualiza con las piezas
+// que cambian en cada jugada; la salida mira primero el acumulador del bando
+// que mueve y luego el del rival.
+pub const INPUTS: usize = 768;
+pub const MAX_HIDDEN: usize = 4096;
+
+// Cuantización: la capa oculta en unidades de 1/QA y la salida de 1/(QA*QB)
+pub const QA: i32 = 255;
+pub const QB: i32 = 64;
+// Centipeones por unidad de salida de la red
+pub const SCALE: i32 = 400;
+
+const MAGIC: &[u8; 4] = b"CBNN";
+const VERSION: u32 = 1;
+const HEADER_SIZE: usize = 12;
+
+// Formato del archivo (todo en little endian): "CBNN", versión (u32), N (u32),
+// pesos de entrada (i16, N por cada entrada), sesgos ocultos (N x i16), pesos
+// de salida (2N x i16: bando que mueve y rival) y sesgo de salida (i32)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Network {
+    pub hidden: usize,
+    pub feature_weights: Vec<i16>,
+    pub feature_bias: Vec<i16>,
+    pub output_weights: Vec<i16>,
+    pub output_bias: i32,
+}
+
+impl Network {
+    pub fn load(path: &str) -> Result<Network, String> {
+        let bytes = fs::read(path).map_err(|e| format!("No se pudo leer {}: {}", path, e))?;
+        Network::from_bytes(&bytes)
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Network, String> {
+        if bytes.len() < HEADER_SIZE || &bytes[..4] != MAGIC {
+            return Err("Red inválida: no es un archivo CBNN".to_string());
+        }
+        let read_u32 = |at: usize| u32::from_le_bytes(bytes[at..at + 4].try_into().unwrap());
+        let version = read_u32(4);
+        if version != VERSION {
+            return Err(format!("Red inválida: versión {} no soportada", version));
+        }
+        let hidden = read_u32(8) as usize;
+        if hidden == 0 || hidden > MAX_HIDDEN {
+            return Err(format!("Red inválida: {} neuronas ocultas", hidden));
+        }
+
+        let expected = HEADER_SIZE + 2 * (INPUTS * hidden + hidden + 2 * hidden) + 4;
+        if bytes.len() != expected {
+            return Err(format!(
+                "Red inválida: {} bytes, se esperaban {}",
+                bytes.len(),
+                expected
+            ));
+        }
+
+        let mut at = HEADER_SIZE;
+        let mut read_i16s = |count: usize| {
+            let values = bytes[at..at + 2 * count]
+                .chunks_exact(2)
+                .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+                .collect::<Vec<i16>>();
+            at += 2 * count;
+            values
+        };
+        let feature_weights = read_i16s(INPUTS * hidden);
+        let feature_bias = read_i16s(hidden);
+        let output_weights = read_i16s(2 * hidden);
+        let output_bias = i32::from_le_bytes(bytes[expected - 4..].try_into().unwrap());
+
+        Ok(Network {
+            hidden,
+            feature_weights,
+            feature_bias,
+            output_weights,
+            output_bias,
+        })
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.hidden as u32).to_le_bytes());
+        for values in [
+            &self.feature_weights,
+            &self.feature_bias,
+            &self.output_weights,
+        ] {
+            for value in values.iter() {
+                bytes.extend_from_slice(&value.to_le_bytes());
+            }
+        }
+        bytes.extend_from_slice(&self.output_bias.to_le_bytes());
+        bytes
+    }
+
+    // Red con pesos aleatorios pequeños, para las pruebas
+    pub fn random(hidden: usize, seed: u64) -> Network {
+        let mut rng = Rng::new(seed);
+        let mut values = |count: usize, range: i64| -> Vec<i16> {
+            (0..count)
+                .map(|_| (rng.below(2 * range as u64 + 1) as i64 - range) as i16)
+                .collect()
+        };
+        Network {
+            hidden,
+            feature_weights: values(INPUTS * hidden, 32),
+            feature_bias: values(hidden, 64),
+            output_weights: values(2 * hidden, 64),
+            output_bias: 1000,
+        }
+    }
+
+    fn weights(&self, feature: usize) -> &[i16] {
+        &self.feature_weights[feature * self.hidden..(feature + 1) * self.hidden]
+    }
+
+    // Evaluación desde cero, desde el punto de vista del bando que mueve
+    pub fn evaluate(&self, board: &Board) -> i32 {
+        let mut accumulator = Accumulator::new(self.hidden);
+        accumulator.refresh(self, board);
+        self.output(&accumulator, board.turn)
+    }
+
+    // Capa de salida sobre los acumuladores ya calculados
+    pub fn output(&self, accumulator: &Accumulator, turn: Color) -> i32 {
+        let us = &accumulator.values[turn as usize];
+        let them = &accumulator.values[turn.opposite() as usize];
+        let (our_weights, their_weights) = self.output_weights.split_at(self.hidden);
+        let sum = simd::crelu_dot(us, our_weights)
+            + simd::crelu_dot(them, their_weights)
+            + self.output_bias;
+        sum * SCALE / (QA * QB)
+    }
+}
+
+// Entrada de una pieza en una casilla vista por `perspective`: primero las
+// piezas propias y después las rivales, con el tablero volteado para las negras
+pub fn feature_index(perspective: Color, piece: Piece, square: Square) -> usize {
+    let side = (piece.color != perspective) as usize;
+    let square = match perspective {
+        Color::White => square,
+        Color::Black => square ^ 56,
+    };
+    side * 384 + piece.piece_type as usize * 64 + square
+}
+
+// Capa oculta (antes de la activación) de las dos perspectivas: [blancas, negras]
+#[derive(Debug, Clone, PartialEq)]
+pub struct Accumulator {
+    pub values: [Vec<i16>; 2],
+}
+
+impl Accumulator {
+    pub fn new(hidden: usize) -> Self {
+        Accumulator {
+            values: [vec![0; hidden], vec![0; hidden]],
+        }
+    }
+
+    // Recalcula las dos perspectivas con todas las piezas del tablero
+    pub fn refresh(&mut self, net: &Network, board: &Board) {
+        for values in self.values.iter_mut() {
+            values.copy_from_slice(&net.feature_bias);
+        }
+        for (square, piece) in board.squares.iter().enumerate() {
+            if let Some(piece) = piece {
+                self.add_piece(net, *piece, square);
+            }
+        }
+    }
+
+    // Actualización incremental: parte del acumulador del padre y solo suma
+    // y resta lo que cambia con la jugada en `board` (la posición de antes):
+    // la pieza que mueve (o en la que corona), la capturada, también al paso,
+    // y la torre del enroque
+    pub fn update(&mut self, parent: &Accumulator, net: &Network, board: &Board, mv: &Move) {
+        for (values, parent) in self.values.iter_mut().zip(&parent.values) {
+            values.copy_from_slice(parent);
+        }
+        let Some(piece) = board.squares[mv.from] else {
+            return;
+        };
+        let placed = match mv.promotion {
+            Some(promotion) => Piece::new(piece.color, promotion),
+            None => piece,
+        };
+        self.remove_piece(net, piece, mv.from);
+        self.add_piece(net, placed, mv.to);
+
+        if let Some(captured) = board.squares[mv.to] {
+            self.remove_piece(net, captured, mv.to);
+        } else if piece.piece_type == PieceType::Pawn && Some(mv.to) == board.en_passant_target {
+            // El peón capturado al paso está en la fila de origen, en la columna de destino
+            let captured = Piece::new(piece.color.opposite(), PieceType::Pawn);
+            self.remove_piece(net, captured, mv.from - mv.from % 8 + mv.to % 8);
+        }
+
+        let (from_file, to_file) = (mv.from % 8, mv.to % 8);
+        if piece.piece_type == PieceType::King && from_file.abs_diff(to_file) == 2 {
+            let rank = mv.to - to_file;
+            let (rook_from, rook_to) = if to_file == 6 {
+                (rank + 7, rank + 5)
+            } else {
+                (rank, rank + 3)
+            };
+            let rook = Piece::new(piece.color, PieceType::Rook);
+            self.remove_piece(net, rook, rook_from);
+            self.add_piece(net, rook, rook_to);
+        }
+    }
+
+    fn add_piece(&mut self, net: &Network, piece: Piece, square: Square) {
+        for perspective in [Color::White, Color::Black] {
+            let weights = net.weights(feature_index(perspective, piece, square));
+            simd::add(&mut self.values[perspective as usize], weights);
+        }
+    }
+
+    fn remove_piece(&mut self, net: &Network, piece: Piece, square: Square) {
+        for perspective in [Color::White, Color::Black] {
+            let weights = net.weights(feature_index(perspective, piece, square));
+            simd::sub(&mut self.values[perspective as usize], weights);
+        }
+    }
+}
+
+// Pila de acumuladores, uno por ply: la búsqueda copia el tablero en cada
+// jugada en lugar de deshacerla, así que el acumulador del hijo se calcula a
+// partir del del padre y el del padre sigue ahí al volver
+pub struct AccumulatorStack {
+    stack: Vec<Accumulator>,
+}
+
+impl AccumulatorStack {
+    pub fn new(hidden: usize, plies: usize) -> Self {
+        AccumulatorStack {
+            stack: vec![Accumulator::new(hidden); plies],
+        }
+    }
+
+    pub fn hidden(&self) -> usize {
+        self.stack[0].values[0].len()
+    }
+
+    pub fn refresh(&mut self, net: &Network, board: &Board) {
+        self.stack[0].refresh(net, board);
+    }
+
+    // Acumulador del ply + 1 tras jugar `mv` en `board`
+    pub fn push(&mut self, ply: usize, net: &Network, board: &Board, mv: &Move) {
+        let (parents, children) = self.stack.split_at_mut(ply + 1);
+        children[0].update(&parents[ply], net, board, mv);
+    }
+
+    // Tras un movimiento nulo las piezas no cambian
+    pub fn push_null(&mut self, ply: usize) {
+        let (parents, children) = self.stack.split_at_mut(ply + 1);
+        children[0].clone_from(&parents[ply]);
+    }
+
+    pub fn get(&self, ply: usize) -> &Accumulator {
+        &self.stack[ply]
+    }
+}
+
+// Operaciones vectoriales: AVX2 si la CPU lo tiene, si no la versión escalar.
+// Las dos dan exactamente el mismo resultado.
+pub mod simd {
+    use super::QA;
+
+    pub fn add(values: &mut [i16], weights: &[i16]) {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: la CPU tiene AVX2
+            unsafe { avx2::add(values, weights) };
+            return;
+        }
+        scalar::add(values, weights);
+    }
+
+    pub fn sub(values: &mut [i16], weights: &[i16]) {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: la CPU tiene AVX2
+            unsafe { avx2::sub(values, weights) };
+            return;
+        }
+        scalar::sub(values, weights);
+    }
+
+    // Producto escalar de la activación (ReLU recortada a [0, QA]) por los pesos
+    pub fn crelu_dot(values: &[i16], weights: &[i16]) -> i32 {
+        #[cfg(target_arch = "x86_64")]
+        if is_x86_feature_detected!("avx2") {
+            // SAFETY: la CPU tiene AVX2
+            return unsafe { avx2::crelu_dot(values, weights) };
+        }
+        scalar::crelu_dot(values, weights)
+    }
+
+    pub mod scalar {
+        use super::QA;
+
+        pub fn add(values: &mut [i16], weights: &[i16]) {
+            for (value, weight) in values.iter_mut().zip(weights) {
+                *value = value.wrapping_add(*weight);
+            }
+        }
+
+        pub fn sub(values: &mut [i16], weights: &[i16]) {
+            for (value, weight) in values.iter_mut().zip(weights) {
+                *value = value.wrapping_sub(*weight);
+            }
+        }
+
+        pub fn crelu_dot(values: &[i16], weights: &[i16]) -> i32 {
+            values
+                .iter()
+                .zip(weights)
+                .map(|(value, weight)| (*value as i32).clamp(0, QA) * *weight as i32)
+                .fold(0, i32::wrapping_add)
+        }
+    }
+
+    #[cfg(target_arch = "x86_64")]
+    mod avx2 {
+        use super::{QA, scalar};
+        use std::arch::x86_64::*;
+
+        // i16 por registro de 256 bits
+        const LANES: usize = 16;
+
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn add(values: &mut [i16], weights: &[i16]) {
+            let chunks = values.len().min(weights.len()) / LANES * LANES;
+            for i in (0..chunks).step_by(LANES) {
+                // SAFETY: i + LANES <= chunks, dentro de los dos slices
+                unsafe {
+                    let value = _mm256_loadu_si256(values.as_ptr().add(i) as *const __m256i);
+                    let weight = _mm256_loadu_si256(weights.as_ptr().add(i) as *const __m256i);
+                    let sum = _mm256_add_epi16(value, weight);
+                    _mm256_storeu_si256(values.as_mut_ptr().add(i) as *mut __m256i, sum);
+                }
+            }
+            scalar::add(&mut values[chunks..], &weights[chunks..]);
+        }
+
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn sub(values: &mut [i16], weights: &[i16]) {
+            let chunks = values.len().min(weights.len()) / LANES * LANES;
+            for i in (0..chunks).step_by(LANES) {
+                // SAFETY: i + LANES <= chunks, dentro de los dos slices
+                unsafe {
+                    let value = _mm256_loadu_si256(values.as_ptr().add(i) as *const __m256i);
+                    let weight = _mm256_loadu_si256(weights.as_ptr().add(i) as *const __m256i);
+                    let difference = _mm256_sub_epi16(value, weight);
+                    _mm256_storeu_si256(values.as_mut_ptr().add(i) as *mut __m256i, difference);
+                }
+            }
+            scalar::sub(&mut values[chunks..], &weights[chunks..]);
+        }
+
+        #[target_feature(enable = "avx2")]
+        pub unsafe fn crelu_dot(values: &[i16], weights: &[i16]) -> i32 {
+            let chunks = values.len().min(weights.len()) / LANES * LANES;
+            let zero = _mm256_setzero_si256();
+            let max = _mm256_set1_epi16(QA as i16);
+            let mut sum = _mm256_setzero_si256();
+            for i in (0..chunks).step_by(LANES) {
+                // SAFETY: i + LANES <= chunks, dentro de los dos slices
+                unsafe {
+                    let value = _mm256_loadu_si256(values.as_ptr().add(i) as *const __m256i);
+                    let weight = _mm256_loadu_si256(weights.as_ptr().add(i) as *const __m256i);
+                    let clipped = _mm256_min_epi16(_mm256_max_epi16(value, zero), max);
+                    // Multiplica por parejas y suma cada pareja en 32 bits
+                    sum = _mm256_add_epi32(sum, _mm256_madd_epi16(clipped, weight));
+                }
+            }
+            let mut lanes = [0i32; 8];
+            // SAFETY: `lanes` tiene sitio para 256 bits
+            unsafe { _mm256_storeu_si256(lanes.as_mut_ptr() as *mut __m256i, sum) };
+            lanes.iter().fold(
+                scalar::crelu_dot(&values[chunks..], &weights[chunks..]),
+                |total, lane| total.wrapping_add(*lane),
+            )
+        }
+    }
+}
@@ -0,0 +1,282 @@
+use std::fmt;
+
+// Nombres de las opciones que anuncia el motor (UCI no distingue mayúsculas)
+pub const HASH: &str = "Hash";
+pub const THREADS: &str = "Threads";
+pub const MULTI_PV: &str = "MultiPV";
+pub const MOVE_OVERHEAD: &str = "Move Overhead";
+pub const PONDER: &str = "Ponder";
+pub const UCI_CHESS960: &str = "UCI_Chess960";
+pub const UCI_LIMIT_STRENGTH: &str = "UCI_LimitStrength";
+pub const UCI_ELO: &str = "UCI_Elo";
+pub const SKILL_LEVEL: &str = "Skill Level";
+// Semilla de las decisiones aleatorias del nivel reducido (0 = usar el reloj)
+pub const SKILL_SEED: &str = "Skill Seed";
+pub const CLEAR_HASH: &str = "Clear Hash";
+// Evaluación con la red de EvalFile en lugar de la clásica
+pub const USE_NNUE: &str = "Use NNUE";
+pub const EVAL_FILE: &str = "EvalFile";
+// Directorio con las tablas de finales generadas con "chess-bot-gm tablebase"
+pub const TABLEBASE_PATH: &str = "TablebasePath";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionKind {
+    Spin {
+        default: i64,
+        min: i64,
+        max: i64,
+    },
+    Check {
+        default: bool,
+    },
+    Combo {
+        default: &'static str,
+        vars: &'static [&'static str],
+    },
+    Button,
+    String {
+        default: &'static str,
+    },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum OptionValue {
+    Spin(i64),
+    Check(bool),
+    Combo(String),
+    Button,
+    String(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct EngineOption {
+    pub name: &'static str,
+    pub kind: OptionKind,
+    pub value: OptionValue,
+}
+
+impl EngineOption {
+    pub fn new(name: &'static str, kind: OptionKind) -> Self {
+        let value = match &kind {
+            OptionKind::Spin { default, .. } => OptionValue::Spin(*default),
+            OptionKind::Check { default } => OptionValue::Check(*default),
+            OptionKind::Combo { default, .. } => OptionValue::Combo(default.to_string()),
+            OptionKind::Button => OptionValue::Button,
+            OptionKind::String { default } => OptionValue::String(default.to_string()),
+        };
+        EngineOption { name, kind, value }
+    }
+
+    // Valida el texto recibido en "setoption" según el tipo de la opción
+    fn parse_value(&self, value: Option<&str>) -> Result<OptionValue, String> {
+        match &self.kind {
+            OptionKind::Button => Ok(OptionValue::Button),
+            OptionKind::Spin { min, max, .. } => {
+                let text = value.ok_or(format!("Falta el valor para '{}'", self.name))?;
+                let number: i64 = text
+                    .parse()
+                    .map_err(|_| format!("Valor inválido para '{}': '{}'", self.name, text))?;
+                if number < *min || number > *max {
+                    return Err(format!(
+                        "Valor fuera de rango para '{}': {} (min {}, max {})",
+                        self.name, number, min, max
+                    ));
+                }
+                Ok(OptionValue::Spin(number))
+            }
+            OptionKind::Check { .. } => {
+                let text = value.ok_or(format!("Falta el valor para '{}'", self.name))?;
+                match text.to_ascii_lowercase().as_str() {
+                    "true" => Ok(OptionValue::Check(true)),
+                    "false" => Ok(OptionValue::Check(false)),
+                    _ => Err(format!("Valor inválido para '{}': '{}'", self.name, text)),
+                }
+            }
+            OptionKind::Combo { vars, .. } => {
+                let text = value.ok_or(format!("Falta el valor para '{}'", self.name))?;
+                vars.iter()
+                    .find(|var| var.eq_ignore_ascii_case(text))
+                    .map(|var| OptionValue::Combo(var.to_string()))
+                    .ok_or(format!("Valor inválido para '{}': '{}'", self.name, text))
+            }
+            OptionKind::String { .. } => {
+                Ok(OptionValue::String(value.unwrap_or_default().to_string()))
+            }
+        }
+    }
+}
+
+impl fmt::Display for EngineOption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "option name {} type ", self.name)?;
+        match &self.kind {
+            OptionKind::Spin { default, min, max } => {
+                write!(f, "spin default {} min {} max {}", default, min, max)
+            }
+            OptionKind::Check { default } => write!(f, "check default {}", default),
+            OptionKind::Combo { default, vars } => {
+                write!(f, "combo default {}", default)?;
+                for var in vars.iter() {
+                    write!(f, " var {}", var)?;
+                }
+                Ok(())
+            }
+            OptionKind::Button => write!(f, "button"),
+            OptionKind::String { default } => {
+                // UCI usa "<empty>" para representar la cadena vacía
+                let default = if default.is_empty() {
+                    "<empty>"
+                } else {
+                    default
+                };
+                write!(f, "string default {}", default)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct EngineOptions {
+    options: Vec<EngineOption>,
+}
+
+impl Default for EngineOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EngineOptions {
+    pub fn new() -> Self {
+        let options = vec![
+            EngineOption::new(
+                HASH,
+                OptionKind::Spin {
+                    default: 16,
+                    min: 1,
+                    max: 4096,
+                },
+            ),
+            EngineOption::new(
+                THREADS,
+                OptionKind::Spin {
+                    default: 1,
+                    min: 1,
+                    max: 256,
+                },
+            ),
+            EngineOption::new(
+                MULTI_PV,
+                OptionKind::Spin {
+                    default: 1,
+                    min: 1,
+                    max: 256,
+                },
+            ),
+            EngineOption::new(
+                MOVE_OVERHEAD,
+                OptionKind::Spin {
+                    default: 10,
+                    min: 0,
+                    max: 5000,
+                },
+            ),
+            EngineOption::new(PONDER, OptionKind::Check { default: false }),
+            EngineOption::new(UCI_CHESS960, OptionKind::Check { default: false }),
+            EngineOption::new(UCI_LIMIT_STRENGTH, OptionKind::Check { default: false }),
+            EngineOption::new(
+                UCI_ELO,
+                OptionKind::Spin {
+                    default: 2400,
+                    min: 800,
+                    max: 2400,
+                },
+            ),
+            EngineOption::new(
+                SKILL_LEVEL,
+                OptionKind::Spin {
+                    default: 20,
+                    min: 0,
+                    max: 20,
+                },
+            ),
+            EngineOption::new(
+                SKILL_SEED,
+                OptionKind::Spin {
+                    default: 0,
+                    min: 0,
+                    max: u32::MAX as i64,
+                },
+            ),
+            EngineOption::new(CLEAR_HASH, OptionKind::Button),
+            EngineOption::new(USE_NNUE, OptionKind::Check { default: false }),
+            EngineOption::new(EVAL_FILE, OptionKind::String { default: "" }),
+            EngineOption::new(TABLEBASE_PATH, OptionKind::String { default: "" }),
+        ];
+
+        EngineOptions { options }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &EngineOption> {
+        self.options.iter()
+    }
+
+    pub fn get(&self, name: &str) -> Option<&EngineOption> {
+        self.options
+            .iter()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+    }
+
+    // Cambia el valor de una opción y devuelve su nombre canónico
+    pub fn set(&mut self, name: &str, value: Option<&str>) -> Result<&'static str, String> {
+        let option = self
+            .options
+            .iter_mut()
+            .find(|option| option.name.eq_ignore_ascii_case(name))
+            .ok_or(format!("Opción desconocida: '{}'", name))?;
+
+        option.value = option.parse_value(value)?;
+        Ok(option.name)
+    }
+
+    pub fn spin(&self, name: &str) -> i64 {
+        match self.get(name).map(|option| &option.value) {
+            Some(OptionValue::Spin(value)) => *value,
+            _ => panic!("La opción '{}' no es de tipo spin", name),
+        }
+    }
+
+    pub fn check(&self, name: &str) -> bool {
+        match self.get(name).map(|option| &option.value) {
+            Some(OptionValue::Check(value)) => *value,
+            _ => panic!("La opción '{}' no es de tipo check", name),
+        }
+    }
+
+    pub fn text(&self, name: &str) -> &str {
+        match self.get(name).map(|option| &option.value) {
+            Some(OptionValue::Combo(value)) | Some(OptionValue::String(value)) => value,
+            _ => panic!("La opción '{}' no es de tipo combo o string", name),
+        }
+    }
+}
+
+// Separa "setoption name <nombre> [value <valor>]" en nombre y valor.
+// Tanto el nombre como el valor pueden tener varias palabras.
+pub fn parse_setoption(parts: &[&str]) -> Result<(String, Option<String>), String> {
+    let name_pos = parts
+        .iter()
+        .position(|&token| token == "name")
+        .ok_or("setoption sin 'name'".to_string())?;
+    let value_pos = parts.iter().position(|&token| token == "value");
+
+    let name_end = value_pos.unwrap_or(parts.len());
+    if name_end <= name_pos + 1 {
+        return Err("setoption sin nombre de opción".to_string());
+    }
+    let name = parts[name_pos + 1..name_end].join(" ");
+
+    let value = value_pos.map(|pos| parts[pos + 1..].join(" "));
+
+    Ok((name, value))
+}
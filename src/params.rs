@@ -0,0 +1,487 @@
+use crate::evaluation::Score;
+use std::fmt::Write;
+
+// Los valores viven en un archivo aparte, con el formato exacto que escribe el
+// ajuste (cargo fmt no entra en los archivos de include!)
+include!("eval_values.rs");
+
+// Posición de cada grupo de parámetros en el vector
+pub mod idx {
+    pub const MATERIAL: usize = 0;
+    pub const PSQT: usize = MATERIAL + 6;
+    pub const DOUBLED: usize = PSQT + 6 * 64;
+    pub const ISOLATED: usize = DOUBLED + 1;
+    pub const BACKWARD: usize = ISOLATED + 1;
+    pub const ISLAND: usize = BACKWARD + 1;
+    pub const CONNECTED: usize = ISLAND + 1;
+    pub const PASSED_RANK: usize = CONNECTED + 8;
+    pub const PASSED_FREE_STOP: usize = PASSED_RANK + 8;
+    pub const PASSED_FREE_PATH: usize = PASSED_FREE_STOP + 1;
+    pub const PASSED_ENEMY_KING_DISTANCE: usize = PASSED_FREE_PATH + 1;
+    pub const PASSED_OWN_KING_DISTANCE: usize = PASSED_ENEMY_KING_DISTANCE + 1;
+    pub const SHIELD: usize = PASSED_OWN_KING_DISTANCE + 1;
+    pub const SHIELD_MISSING: usize = SHIELD + 3;
+    pub const STORM: usize = SHIELD_MISSING + 1;
+    pub const SEMI_OPEN_FILE: usize = STORM + 8;
+    pub const OPEN_FILE: usize = SEMI_OPEN_FILE + 1;
+    pub const SAFETY_TABLE: usize = OPEN_FILE + 1;
+    pub const MOBILITY: usize = SAFETY_TABLE + 100;
+    pub const ROOK_OPEN_FILE: usize = MOBILITY + 6;
+    pub const ROOK_SEMI_OPEN_FILE: usize = ROOK_OPEN_FILE + 1;
+    pub const ROOK_SEVENTH: usize = ROOK_SEMI_OPEN_FILE + 1;
+    pub const KNIGHT_OUTPOST: usize = ROOK_SEVENTH + 1;
+    pub const BISHOP_PAIR: usize = KNIGHT_OUTPOST + 1;
+    pub const BAD_BISHOP_PAWN: usize = BISHOP_PAIR + 1;
+    pub const END: usize = BAD_BISHOP_PAWN + 1;
+}
+
+pub const NUM_PARAMS: usize = idx::END;
+
+// Cómo se escribe cada grupo en eval_values.rs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupKind {
+    // Un Score
+    Single,
+    // Un array de Score
+    Array,
+    // Las doce tablas pieza-casilla (medio juego y final de cada pieza)
+    PieceSquare,
+    // Array de enteros que solo cuentan en el medio juego
+    MgArray,
+    // Entero que solo cuenta en el final
+    EgSingle,
+}
+
+pub struct ParamGroup {
+    pub name: &'static str,
+    pub comment: &'static str,
+    pub offset: usize,
+    pub len: usize,
+    pub kind: GroupKind,
+}
+
+const fn group(
+    name: &'static str,
+    comment: &'static str,
+    offset: usize,
+    len: usize,
+    kind: GroupKind,
+) -> ParamGroup {
+    ParamGroup {
+        name,
+        comment,
+        offset,
+        len,
+        kind,
+    }
+}
+
+use GroupKind::*;
+
+pub const GROUPS: [ParamGroup; 25] = [
+    group(
+        "MATERIAL",
+        "Valor de cada pieza (peón, caballo, alfil, torre, dama, rey)",
+        idx::MATERIAL,
+        6,
+        Array,
+    ),
+    group(
+        "PSQT",
+        "Tablas pieza-casilla desde las blancas, con la octava fila arriba",
+        idx::PSQT,
+        6 * 64,
+        PieceSquare,
+    ),
+    group(
+        "DOUBLED",
+        "Por cada peón de más en una columna",
+        idx::DOUBLED,
+        1,
+        Single,
+    ),
+    group(
+        "ISOLATED",
+        "Peón sin peones propios en las columnas vecinas",
+        idx::ISOLATED,
+        1,
+        Single,
+    ),
+    group(
+        "BACKWARD",
+        "Peón retrasado: sin apoyo y con la casilla de delante controlada",
+        idx::BACKWARD,
+        1,
+        Single,
+    ),
+    group(
+        "ISLAND",
+        "Por cada isla de peones además de la primera",
+        idx::ISLAND,
+        1,
+        Single,
+    ),
+    group(
+        "CONNECTED",
+        "Peón defendido o en falange, según su fila relativa",
+        idx::CONNECTED,
+        8,
+        Array,
+    ),
+    group(
+        "PASSED_RANK",
+        "Peón pasado según su fila relativa",
+        idx::PASSED_RANK,
+        8,
+        Array,
+    ),
+    group(
+        "PASSED_FREE_STOP",
+        "Pasado con la casilla de delante libre, por fila de avance",
+        idx::PASSED_FREE_STOP,
+        1,
+        Single,
+    ),
+    group(
+        "PASSED_FREE_PATH",
+        "Pasado con todo el camino libre, por fila de avance",
+        idx::PASSED_FREE_PATH,
+        1,
+        Single,
+    ),
+    group(
+        "PASSED_ENEMY_KING_DISTANCE",
+        "Final: por casilla de distancia del rey rival a la de delante del pasado",
+        idx::PASSED_ENEMY_KING_DISTANCE,
+        1,
+        EgSingle,
+    ),
+    group(
+        "PASSED_OWN_KING_DISTANCE",
+        "Final: por casilla de distancia del rey propio a la de delante del pasado",
+        idx::PASSED_OWN_KING_DISTANCE,
+        1,
+        EgSingle,
+    ),
+    group(
+        "SHIELD",
+        "Peón propio delante del rey a 1 o 2 filas",
+        idx::SHIELD,
+        3,
+        Array,
+    ),
+    group(
+        "SHIELD_MISSING",
+        "Columna junto al rey sin peón que lo cubra",
+        idx::SHIELD_MISSING,
+        1,
+        Single,
+    ),
+    group(
+        "STORM",
+        "Peón rival que avanza hacia el rey, según a cuántas filas está",
+        idx::STORM,
+        8,
+        Array,
+    ),
+    group(
+        "SEMI_OPEN_FILE",
+        "Columna junto al rey sin peones propios",
+        idx::SEMI_OPEN_FILE,
+        1,
+        Single,
+    ),
+    group(
+        "OPEN_FILE",
+        "Columna junto al rey sin peones",
+        idx::OPEN_FILE,
+        1,
+        Single,
+    ),
+    group(
+        "SAFETY_TABLE",
+        "Medio juego: ataque sobre el rey según las unidades de ataque",
+        idx::SAFETY_TABLE,
+        100,
+        MgArray,
+    ),
+    group(
+        "MOBILITY",
+        "Por casilla útil sobre la base (caballo, alfil, torre, dama)",
+        idx::MOBILITY,
+        6,
+        Array,
+    ),
+    group(
+        "ROOK_OPEN_FILE",
+        "Torre en columna sin peones",
+        idx::ROOK_OPEN_FILE,
+        1,
+        Single,
+    ),
+    group(
+        "ROOK_SEMI_OPEN_FILE",
+        "Torre en columna sin peones propios",
+        idx::ROOK_SEMI_OPEN_FILE,
+        1,
+        Single,
+    ),
+    group(
+        "ROOK_SEVENTH",
+        "Torre en séptima con el rey en la octava o peones que atacar",
+        idx::ROOK_SEVENTH,
+        1,
+        Single,
+    ),
+    group(
+        "KNIGHT_OUTPOST",
+        "Caballo en un puesto avanzado",
+        idx::KNIGHT_OUTPOST,
+        1,
+        Single,
+    ),
+    group(
+        "BISHOP_PAIR",
+        "Pareja de alfiles",
+        idx::BISHOP_PAIR,
+        1,
+        Single,
+    ),
+    group(
+        "BAD_BISHOP_PAWN",
+        "Por cada peón propio en casillas del color del alfil",
+        idx::BAD_BISHOP_PAWN,
+        1,
+        Single,
+    ),
+];
+
+const PIECE_NAMES: [&str; 6] = ["PAWN", "KNIGHT", "BISHOP", "ROOK", "QUEEN", "KING"];
+const MG_TABLES: [&[i32; 64]; 6] = [
+    &MG_PAWN, &MG_KNIGHT, &MG_BISHOP, &MG_ROOK, &MG_QUEEN, &MG_KING,
+];
+const EG_TABLES: [&[i32; 64]; 6] = [
+    &EG_PAWN, &EG_KNIGHT, &EG_BISHOP, &EG_ROOK, &EG_QUEEN, &EG_KING,
+];
+
+// Todos los parámetros de la evaluación en un vector. La evaluación es lineal
+// en ellos: cada término suma un parámetro multiplicado por un número de veces.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvalParams {
+    pub values: [Score; NUM_PARAMS],
+}
+
+impl EvalParams {
+    const fn from_constants() -> Self {
+        let mut values = [Score::ZERO; NUM_PARAMS];
+        copy(&mut values, idx::MATERIAL, &MATERIAL);
+
+        let mut piece = 0;
+        while piece < 6 {
+            let mut square = 0;
+            while square < 64 {
+                values[idx::PSQT + piece * 64 + square] =
+                    Score::new(MG_TABLES[piece][square], EG_TABLES[piece][square]);
+                square += 1;
+            }
+            piece += 1;
+        }
+
+        values[idx::DOUBLED] = DOUBLED;
+        values[idx::ISOLATED] = ISOLATED;
+        values[idx::BACKWARD] = BACKWARD;
+        values[idx::ISLAND] = ISLAND;
+        copy(&mut values, idx::CONNECTED, &CONNECTED);
+        copy(&mut values, idx::PASSED_RANK, &PASSED_RANK);
+        values[idx::PASSED_FREE_STOP] = PASSED_FREE_STOP;
+        values[idx::PASSED_FREE_PATH] = PASSED_FREE_PATH;
+        values[idx::PASSED_ENEMY_KING_DISTANCE] = Score::new(0, PASSED_ENEMY_KING_DISTANCE);
+        values[idx::PASSED_OWN_KING_DISTANCE] = Score::new(0, PASSED_OWN_KING_DISTANCE);
+        copy(&mut values, idx::SHIELD, &SHIELD);
+        values[idx::SHIELD_MISSING] = SHIELD_MISSING;
+        copy(&mut values, idx::STORM, &STORM);
+        values[idx::SEMI_OPEN_FILE] = SEMI_OPEN_FILE;
+        values[idx::OPEN_FILE] = OPEN_FILE;
+
+        let mut i = 0;
+        while i < SAFETY_TABLE.len() {
+            values[idx::SAFETY_TABLE + i] = Score::new(SAFETY_TABLE[i], 0);
+            i += 1;
+        }
+
+        copy(&mut values, idx::MOBILITY, &MOBILITY);
+        values[idx::ROOK_OPEN_FILE] = ROOK_OPEN_FILE;
+        values[idx::ROOK_SEMI_OPEN_FILE] = ROOK_SEMI_OPEN_FILE;
+        values[idx::ROOK_SEVENTH] = ROOK_SEVENTH;
+        values[idx::KNIGHT_OUTPOST] = KNIGHT_OUTPOST;
+        values[idx::BISHOP_PAIR] = BISHOP_PAIR;
+        values[idx::BAD_BISHOP_PAWN] = BAD_BISHOP_PAWN;
+
+        EvalParams { values }
+    }
+
+    // Si el ajuste puede mover la componente de medio juego (0) o de final (1)
+    // de un parámetro. El rey no tiene valor material y los términos de una
+    // sola fase se quedan en su fase.
+    pub fn is_tunable(index: usize, phase: usize) -> bool {
+        if index == idx::MATERIAL + 5 {
+            return false;
+        }
+        // La seguridad del rey solo cuenta en el medio juego
+        if (idx::SHIELD..idx::MOBILITY).contains(&index) {
+            return phase == 0;
+        }
+        let group = GROUPS
+            .iter()
+            .find(|group| (group.offset..group.offset + group.len).contains(&index))
+            .expect("Índice de parámetro fuera de rango");
+        match group.kind {
+            MgArray => phase == 0,
+            EgSingle => phase == 1,
+            _ => true,
+        }
+    }
+
+    // Código de eval_values.rs con estos valores
+    pub fn rust_source(&self) -> String {
+        let mut out = String::new();
+        out.push_str(
+            "// Parámetros de la evaluación. `chess-bot-gm tune` genera este archivo con el\n",
+        );
+        out.push_str(
+            "// mismo formato: se puede sustituir entero por su salida o retocar a mano.\n",
+        );
+
+        for group in GROUPS.iter() {
+            let values = &self.values[group.offset..group.offset + group.len];
+            let _ = writeln!(out, "\n// {}", group.comment);
+            match group.kind {
+                Single => {
+                    let _ = writeln!(
+                        out,
+                        "pub const {}: Score = Score::new({}, {});",
+                        group.name, values[0].mg, values[0].eg
+                    );
+                }
+                Array => {
+                    let _ = writeln!(out, "pub const {}: [Score; {}] = [", group.name, group.len);
+                    for value in values {
+                        let _ = writeln!(out, "    Score::new({}, {}),", value.mg, value.eg);
+                    }
+                    out.push_str("];\n");
+                }
+                PieceSquare => {
+                    for (piece, name) in PIECE_NAMES.iter().enumerate() {
+                        let table = &values[piece * 64..(piece + 1) * 64];
+                        if piece > 0 {
+                            out.push('\n');
+                        }
+                        let mg: Vec<i32> = table.iter().map(|value| value.mg).collect();
+                        write_table(&mut out, &format!("MG_{}", name), &mg, 8);
+                        out.push('\n');
+                        let eg: Vec<i32> = table.iter().map(|value| value.eg).collect();
+                        write_table(&mut out, &format!("EG_{}", name), &eg, 8);
+                    }
+                }
+                MgArray => {
+                    let mg: Vec<i32> = values.iter().map(|value| value.mg).collect();
+                    write_table(&mut out, group.name, &mg, 10);
+                }
+                EgSingle => {
+                    let _ = writeln!(out, "pub const {}: i32 = {};", group.name, values[0].eg);
+                }
+            }
+        }
+        out
+    }
+}
+
+const fn copy(values: &mut [Score; NUM_PARAMS], offset: usize, source: &[Score]) {
+    let mut i = 0;
+    while i < source.len() {
+        values[offset + i] = source[i];
+        i += 1;
+    }
+}
+
+fn write_table(out: &mut String, name: &str, values: &[i32], per_row: usize) {
+    let _ = writeln!(out, "pub const {}: [i32; {}] = [", name, values.len());
+    for row in values.chunks(per_row) {
+        out.push_str("   ");
+        for value in row {
+            let _ = write!(out, " {:>4},", value);
+        }
+        out.push('\n');
+    }
+    out.push_str("];\n");
+}
+
+// Los parámetros con los que juega el motor
+pub static PARAMS: EvalParams = EvalParams::from_constants();
+
+// Destino de los términos de la evaluación: cada término anota qué parámetro
+// usa y cuántas veces (con signo: negativo si favorece al rival)
+pub trait Accumulator {
+    fn add(&mut self, index: usize, count: i32);
+}
+
+// La evaluación normal: va sumando los valores de los parámetros
+pub struct ScoreSum<'a> {
+    params: &'a EvalParams,
+    pub score: Score,
+}
+
+impl<'a> ScoreSum<'a> {
+    pub fn new(params: &'a EvalParams) -> Self {
+        ScoreSum {
+            params,
+            score: Score::ZERO,
+        }
+    }
+}
+
+impl Accumulator for ScoreSum<'_> {
+    #[inline]
+    fn add(&mut self, index: usize, count: i32) {
+        self.score += self.params.values[index] * count;
+    }
+}
+
+// Para el ajuste: cuántas veces aparece cada parámetro en la posición
+pub struct Coefficients {
+    pub counts: Vec<i32>,
+}
+
+impl Default for Coefficients {
+    fn default() -> Self {
+        Coefficients {
+            counts: vec![0; NUM_PARAMS],
+        }
+    }
+}
+
+impl Accumulator for Coefficients {
+    fn add(&mut self, index: usize, count: i32) {
+        self.counts[index] += count;
+    }
+}
+
+// Los términos de un bando se escriben en positivo; para las negras se pasan
+// por aquí y cambian de signo
+pub struct Side<'a, A: Accumulator> {
+    inner: &'a mut A,
+    sign: i32,
+}
+
+impl<'a, A: Accumulator> Side<'a, A> {
+    pub fn new(inner: &'a mut A, sign: i32) -> Self {
+        Side { inner, sign }
+    }
+}
+
+impl<A: Accumulator> Accumulator for Side<'_, A> {
+    #[inline]
+    fn add(&mut self, index: usize, count: i32) {
+        self.inner.add(index, count * self.sign);
+    }
+}
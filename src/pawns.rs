@@ -0,0 +1,285 @@
+use crate::{
+    evaluation::Score,
+    params::{Accumulator, PARAMS, ScoreSum, Side, idx},
+    types::{Board, Color, PieceType, Square, distance, relative_rank},
+};
+
+pub const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+fn color_index(color: Color) -> usize {
+    color as usize
+}
+
+// Máscara de las filas por delante de `rank` para el bando
+fn ranks_ahead(rank: usize, color: Color) -> u8 {
+    match color {
+        Color::White => !((2u16 << rank) - 1) as u8,
+        Color::Black => ((1u16 << rank) - 1) as u8,
+    }
+}
+
+// Peones de cada bando por columna: el bit r de `files[color][f]` indica un
+// peón en la fila r. Todas las preguntas sobre la estructura salen de aquí.
+#[derive(Debug, Clone, Copy)]
+pub struct PawnStructure {
+    files: [[u8; 8]; 2],
+}
+
+impl PawnStructure {
+    pub fn new(board: &Board) -> Self {
+        let mut files = [[0; 8]; 2];
+        for (square, piece) in board.squares.iter().enumerate() {
+            if let Some(piece) = piece
+                && piece.piece_type == PieceType::Pawn
+            {
+                files[color_index(piece.color)][square % 8] |= 1 << (square / 8);
+            }
+        }
+        PawnStructure { files }
+    }
+
+    fn file(&self, color: Color, file: usize) -> u8 {
+        self.files[color_index(color)][file]
+    }
+
+    // Peones del bando en las columnas vecinas
+    fn adjacent(&self, color: Color, file: usize) -> u8 {
+        let left = if file > 0 {
+            self.file(color, file - 1)
+        } else {
+            0
+        };
+        let right = if file < 7 {
+            self.file(color, file + 1)
+        } else {
+            0
+        };
+        left | right
+    }
+
+    fn has_pawn(&self, color: Color, rank: usize, file: usize) -> bool {
+        rank < 8 && self.file(color, file) & (1 << rank) != 0
+    }
+
+    pub fn is_doubled(&self, square: Square, color: Color) -> bool {
+        self.file(color, square % 8).count_ones() > 1
+    }
+
+    pub fn is_isolated(&self, square: Square, color: Color) -> bool {
+        self.adjacent(color, square % 8) == 0
+    }
+
+    // Ningún peón rival delante en su columna ni en las vecinas
+    pub fn is_passed(&self, square: Square, color: Color) -> bool {
+        let (rank, file) = (square / 8, square % 8);
+        let enemy = color.opposite();
+        let blockers = self.file(enemy, file) | self.adjacent(enemy, file);
+        blockers & ranks_ahead(rank, color) == 0
+    }
+
+    // Defendido por un peón propio o con uno al lado en la misma fila
+    pub fn is_connected(&self, square: Square, color: Color) -> bool {
+        let (rank, file) = (square / 8, square % 8);
+        let behind = match color {
+            Color::White => rank.wrapping_sub(1),
+            Color::Black => rank + 1,
+        };
+        let row_mask = |rank: usize| if rank < 8 { 1u8 << rank } else { 0 };
+        self.adjacent(color, file) & (row_mask(rank) | row_mask(behind)) != 0
+    }
+
+    // Sin peones vecinos a su altura o por detrás que puedan apoyarlo, y con
+    // la casilla de delante controlada por un peón rival: no puede avanzar
+    pub fn is_backward(&self, square: Square, color: Color) -> bool {
+        let (rank, file) = (square / 8, square % 8);
+        let support = self.adjacent(color, file) & !ranks_ahead(rank, color);
+        if self.is_isolated(square, color) || support != 0 {
+            return false;
+        }
+
+        // Un peón rival que ataca la casilla de delante está dos filas más allá
+        let attacker_rank = match color {
+            Color::White => rank + 2,
+            Color::Black => rank.wrapping_sub(2),
+        };
+        let enemy = color.opposite();
+        (file > 0 && self.has_pawn(enemy, attacker_rank, file - 1))
+            || (file < 7 && self.has_pawn(enemy, attacker_rank, file + 1))
+    }
+
+    // Grupos de columnas seguidas con peones del bando
+    pub fn islands(&self, color: Color) -> u32 {
+        let mut islands = 0;
+        let mut in_island = false;
+        for file in 0..8 {
+            let occupied = self.file(color, file) != 0;
+            if occupied && !in_island {
+                islands += 1;
+            }
+            in_island = occupied;
+        }
+        islands
+    }
+
+    // Términos de estructura de un bando (positivos si le favorecen). Anota
+    // además sus peones pasados.
+    fn evaluate<A: Accumulator>(&self, color: Color, passed: &mut u64, acc: &mut A) {
+        for file in 0..8 {
+            let pawns = self.file(color, file);
+            if pawns.count_ones() > 1 {
+                acc.add(idx::DOUBLED, pawns.count_ones() as i32 - 1);
+            }
+
+            for rank in (0..8).filter(|rank| pawns & (1 << rank) != 0) {
+                let square = rank * 8 + file;
+                if self.is_isolated(square, color) {
+                    acc.add(idx::ISOLATED, 1);
+                } else if self.is_backward(square, color) {
+                    acc.add(idx::BACKWARD, 1);
+                }
+                if self.is_connected(square, color) {
+                    acc.add(idx::CONNECTED + relative_rank(square, color), 1);
+                }
+                if self.is_passed(square, color) {
+                    *passed |= 1 << square;
+                }
+            }
+        }
+
+        let islands = self.islands(color) as i32;
+        if islands > 1 {
+            acc.add(idx::ISLAND, islands - 1);
+        }
+    }
+}
+
+// Lo que se guarda de una estructura: su puntuación (desde las blancas) y
+// los peones pasados de cada bando. La parte de los pasados que depende de
+// las piezas y los reyes se calcula en cada evaluación.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PawnEntry {
+    pub key: u64,
+    pub score: Score,
+    pub passed: [u64; 2],
+}
+
+pub fn analyze(board: &Board) -> PawnEntry {
+    let mut sum = ScoreSum::new(&PARAMS);
+    let passed = pawn_structure(board, &mut sum);
+
+    PawnEntry {
+        key: board.pawn_hash,
+        score: sum.score,
+        passed,
+    }
+}
+
+// Estructura de los dos bandos (las negras en negativo). Devuelve los peones
+// pasados de cada uno.
+pub fn pawn_structure<A: Accumulator>(board: &Board, acc: &mut A) -> [u64; 2] {
+    let structure = PawnStructure::new(board);
+    let mut passed = [0; 2];
+    structure.evaluate(Color::White, &mut passed[0], acc);
+    structure.evaluate(Color::Black, &mut passed[1], &mut Side::new(acc, -1));
+    passed
+}
+
+// Términos de estructura de un solo bando (para el desglose de la evaluación)
+pub fn side_pawn_structure<A: Accumulator>(board: &Board, color: Color, acc: &mut A) {
+    PawnStructure::new(board).evaluate(color, &mut 0, acc);
+}
+
+// Tabla de estructuras de peones indexada por el hash de peones. La
+// estructura cambia mucho menos que la posición, así que casi siempre acierta.
+// Cada hilo de búsqueda tiene la suya. Una entrada vacía (clave 0) coincide
+// con la posición sin peones, y su contenido (todo a cero) es el correcto.
+pub struct PawnTable {
+    entries: Vec<PawnEntry>,
+    pub hits: u64,
+}
+
+impl Default for PawnTable {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PawnTable {
+    pub fn new() -> Self {
+        PawnTable {
+            entries: vec![PawnEntry::default(); PAWN_TABLE_SIZE],
+            hits: 0,
+        }
+    }
+
+    pub fn probe(&mut self, board: &Board) -> PawnEntry {
+        let index = board.pawn_hash as usize % self.entries.len();
+        let entry = &mut self.entries[index];
+        if entry.key == board.pawn_hash {
+            self.hits += 1;
+        } else {
+            *entry = analyze(board);
+        }
+        *entry
+    }
+}
+
+// Peones pasados con lo que depende del resto de la posición: si tienen la
+// casilla de delante o todo el camino libres y, en el final, a qué distancia
+// están los reyes de la casilla de delante
+pub fn passed_pawns(board: &Board, entry: &PawnEntry) -> Score {
+    let mut sum = ScoreSum::new(&PARAMS);
+    passed_pawns_for(board, entry, Color::White, &mut sum);
+    passed_pawns_for(board, entry, Color::Black, &mut Side::new(&mut sum, -1));
+    sum.score
+}
+
+pub fn passed_pawns_for<A: Accumulator>(
+    board: &Board,
+    entry: &PawnEntry,
+    color: Color,
+    acc: &mut A,
+) {
+    let mut passed = entry.passed[color_index(color)];
+    let own_king = board.find_king(color);
+    let enemy_king = board.find_king(color.opposite());
+
+    while passed != 0 {
+        let square = passed.trailing_zeros() as Square;
+        passed &= passed - 1;
+
+        let rank = relative_rank(square, color);
+        acc.add(idx::PASSED_RANK + rank, 1);
+        // Desde la cuarta fila cuenta más cuanto más avanzado
+        let weight = rank.saturating_sub(2) as i32;
+        if weight == 0 {
+            continue;
+        }
+
+        let step = match color {
+            Color::White => 8,
+            Color::Black => -8,
+        };
+        let stop = (square as isize + step) as Square;
+        let mut path = (1..8 - rank as isize).map(|i| (square as isize + step * i) as Square);
+
+        if board.squares[stop].is_none() {
+            acc.add(idx::PASSED_FREE_STOP, weight);
+        }
+        if path.all(|square| board.squares[square].is_none()) {
+            acc.add(idx::PASSED_FREE_PATH, weight);
+        }
+
+        // En el final: el rey rival lejos de la casilla de delante y el propio cerca
+        if let (Some(own_king), Some(enemy_king)) = (own_king, enemy_king) {
+            acc.add(
+                idx::PASSED_ENEMY_KING_DISTANCE,
+                distance(enemy_king, stop) as i32 * weight,
+            );
+            acc.add(
+                idx::PASSED_OWN_KING_DISTANCE,
+                distance(own_king, stop) as i32 * weight,
+            );
+        }
+    }
+}
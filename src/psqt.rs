@@ -0,0 +1,44 @@
+use crate::{
+    evaluation::Score,
+    params::{Accumulator, PARAMS, idx},
+    types::{Board, Color, Piece, PieceType, Square},
+};
+
+// Las tablas se leen con la octava fila arriba: para las blancas hay que
+// voltear la casilla y para las negras (que ven el tablero al revés) no.
+fn psqt_index(piece: Piece, square: Square) -> usize {
+    let index = match piece.color {
+        Color::White => square ^ 56,
+        Color::Black => square,
+    };
+    idx::PSQT + piece.piece_type as usize * 64 + index
+}
+
+// Bonificación posicional de una pieza en una casilla, sin el material
+pub fn psqt(piece: Piece, square: Square) -> Score {
+    PARAMS.values[psqt_index(piece, square)]
+}
+
+// Valor de la pieza en la evaluación. SEE y la ordenación usan los valores
+// fijos de `piece_value`.
+pub fn material(piece_type: PieceType) -> Score {
+    PARAMS.values[idx::MATERIAL + piece_type as usize]
+}
+
+pub fn side_material<A: Accumulator>(board: &Board, color: Color, acc: &mut A) {
+    for piece in board.squares.iter().flatten() {
+        if piece.color == color {
+            acc.add(idx::MATERIAL + piece.piece_type as usize, 1);
+        }
+    }
+}
+
+pub fn side_piece_squares<A: Accumulator>(board: &Board, color: Color, acc: &mut A) {
+    for (square, piece) in board.squares.iter().enumerate() {
+        if let Some(piece) = piece
+            && piece.color == color
+        {
+            acc.add(psqt_index(*piece, square), 1);
+        }
+    }
+}
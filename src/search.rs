@@ -1,59 +1,1059 @@
 use crate::{
-    evaluation::evaluate,
-    types::{Board, Color, Move},
+    endgame::{self, Verdict},
+    evaluation::{evaluate_cached, piece_value},
+    kpk::{KpkResult, probe_kpk},
+    movepick::{MoveOrdering, MovePicker},
+    nnue::{AccumulatorStack, Network},
+    pawns::{PawnStructure, PawnTable},
+    see::see,
+    tablebase::{Dtm, Tablebases},
+    timeman::{SearchLimits, TimeManager},
+    tt::{Bound, TranspositionTable},
+    types::{Board, Color, Move, PieceType, relative_rank},
+};
+use std::{
+    sync::{
+        Arc, OnceLock,
+        atomic::{AtomicBool, AtomicU64, Ordering},
+    },
+    thread,
+    time::Duration,
 };
 
-const INFINITY: i32 = 50000;
-const MATE_SCORE: i32 = 49000;
+pub const INFINITY: i32 = 32000;
+pub const MATE_SCORE: i32 = 31000;
+pub const MAX_PLY: i32 = 128;
+// Cualquier puntuación por encima de este umbral es un mate en N jugadas
+pub const MATE_BOUND: i32 = MATE_SCORE - MAX_PLY;
 
-pub fn search_best_move(board: &Board, depth: u32) -> Option<Move> {
-    let moves = board.generate_moves();
-    let mut alpha = -INFINITY;
-    let beta = INFINITY;
-    let mut best_move = None;
+// Margen de seguridad de la poda delta en la búsqueda de quietud
+const DELTA_MARGIN: i32 = 200;
+
+// Semiancho inicial de la ventana de aspiración y profundidad a partir de la que se usa
+const ASPIRATION_WINDOW: i32 = 25;
+const ASPIRATION_MIN_DEPTH: u32 = 4;
+
+// Poda de futilidad inversa: si la evaluación supera beta por este margen
+// por ply restante, suponemos que el nodo va a fallar alto
+const RFP_MAX_DEPTH: u32 = 6;
+const RFP_MARGIN: i32 = 80;
+
+const NMP_MIN_DEPTH: u32 = 3;
+
+// Poda de futilidad: jugadas tranquilas que no pueden subir la evaluación hasta alpha
+const FUTILITY_MAX_DEPTH: u32 = 3;
+const FUTILITY_BASE_MARGIN: i32 = 100;
+const FUTILITY_MARGIN: i32 = 100;
 
-    for mv in moves {
-        let mut new_board = board.clone();
-        new_board.make_move(&mv);
-        let score = -negamax(&new_board, depth - 1, -beta, -alpha);
+// Poda de jugadas tardías: a poca profundidad solo miramos las primeras tranquilas
+const LMP_MAX_DEPTH: u32 = 4;
 
-        if score > alpha {
-            alpha = score;
-            best_move = Some(mv);
+const LMR_MIN_DEPTH: u32 = 3;
+const LMR_MIN_MOVE: usize = 2;
+
+// Extensión singular: solo a partir de esta profundidad y con una entrada de
+// la TT suficientemente profunda
+const SINGULAR_MIN_DEPTH: u32 = 6;
+const SINGULAR_TT_DEPTH_MARGIN: u32 = 3;
+
+// Cada cuántos nodos se comprueba el reloj
+const TIME_CHECK_INTERVAL: u64 = 1024;
+
+pub struct SearchResult {
+    pub best_move: Option<Move>,
+    pub score: i32,
+    pub depth: u32,
+    pub nodes: u64,
+    pub pv: Vec<Move>,
+    // Todas las líneas del MultiPV, de mejor a peor (la primera es la de arriba)
+    pub lines: Vec<PvLine>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct PvLine {
+    pub score: i32,
+    pub pv: Vec<Move>,
+}
+
+// Resultado del análisis de una jugada de la raíz
+#[derive(Debug, Clone, PartialEq)]
+pub struct RootMoveAnalysis {
+    pub mv: Move,
+    // Puntuación exacta (no una cota) desde el punto de vista del que mueve
+    pub score: i32,
+    pub depth: u32,
+    pub pv: Vec<Move>,
+}
+
+#[derive(Debug, Clone)]
+pub struct SearchConfig {
+    // Si es false, las jugadas se recorren en el orden de generación
+    pub move_ordering: bool,
+    // Principal Variation Search (ventana nula para las jugadas que no son la primera)
+    pub pvs: bool,
+    pub aspiration_windows: bool,
+    // Búsqueda selectiva; cada técnica se puede desactivar por separado
+    pub null_move: bool,
+    pub lmr: bool,
+    pub reverse_futility: bool,
+    pub futility: bool,
+    pub late_move_pruning: bool,
+    // Extensiones: las de jaque y singular van activadas, las de recaptura y
+    // peón pasado son opcionales
+    pub check_extension: bool,
+    pub singular_extension: bool,
+    pub recapture_extension: bool,
+    pub passed_pawn_extension: bool,
+    // Número de jugadas de la raíz que se buscan con su propia variante
+    pub multi_pv: usize,
+    // Imprime una línea "info" de UCI por cada iteración
+    pub print_info: bool,
+    // Red con la que evaluar en lugar de la evaluación clásica
+    pub nnue: Option<Arc<Network>>,
+    // Tablas de finales que se consultan en la raíz y dentro del árbol
+    pub tablebases: Option<Arc<Tablebases>>,
+}
+
+impl Default for SearchConfig {
+    fn default() -> Self {
+        SearchConfig {
+            move_ordering: true,
+            pvs: true,
+            aspiration_windows: true,
+            null_move: true,
+            lmr: true,
+            reverse_futility: true,
+            futility: true,
+            late_move_pruning: true,
+            check_extension: true,
+            singular_extension: true,
+            recapture_extension: false,
+            passed_pawn_extension: false,
+            multi_pv: 1,
+            print_info: false,
+            nnue: None,
+            tablebases: None,
         }
     }
+}
+
+pub fn search_best_move(board: &Board, depth: u32) -> Option<Move> {
+    let tt = TranspositionTable::new(1);
+    search(board, depth, &tt).best_move
+}
+
+// Las `count` mejores jugadas de la raíz con su puntuación y variante, de mejor a peor
+pub fn search_multi_pv(board: &Board, depth: u32, count: usize) -> Vec<PvLine> {
+    let tt = TranspositionTable::new(1);
+    tt.new_search();
+    let mut thread = SearchThread::new(&tt);
+    thread.config.multi_pv = count;
+    thread.search(board, depth).lines
+}
 
-    best_move
+// Todas las jugadas legales de la raíz con su puntuación exacta, de mejor a peor
+pub fn analyze_root_moves(board: &Board, depth: u32) -> Vec<RootMoveAnalysis> {
+    let tt = TranspositionTable::new(1);
+    tt.new_search();
+    SearchThread::new(&tt).analyze(board, &SearchLimits::fixed_depth(depth))
 }
 
-fn negamax(board: &Board, depth: u32, mut alpha: i32, beta: i32) -> i32 {
-    let moves = board.generate_moves();
-    if moves.is_empty() {
-        if board.is_king_attacked(board.turn) {
-            return -MATE_SCORE - (depth as i32); // Penaliza más cuanto más profundo estemos
+pub fn search(board: &Board, depth: u32, tt: &TranspositionTable) -> SearchResult {
+    tt.new_search();
+    SearchThread::new(tt).search(board, depth)
+}
+
+// Estado común a todos los hilos de una búsqueda
+#[derive(Debug, Default)]
+pub struct SearchShared {
+    // Cuando se activa, todos los hilos abandonan la búsqueda
+    stop: AtomicBool,
+    // Mientras se piensa en el tiempo del rival (o con "go infinite") no se
+    // mira el reloj ni se termina la búsqueda hasta "ponderhit" o "stop"
+    pondering: AtomicBool,
+    // Nodos de todos los hilos, actualizado cada TIME_CHECK_INTERVAL nodos
+    nodes: AtomicU64,
+}
+
+impl SearchShared {
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stop.load(Ordering::Relaxed)
+    }
+
+    pub fn set_pondering(&self, pondering: bool) {
+        self.pondering.store(pondering, Ordering::Relaxed);
+    }
+
+    pub fn is_pondering(&self) -> bool {
+        self.pondering.load(Ordering::Relaxed)
+    }
+
+    pub fn nodes(&self) -> u64 {
+        self.nodes.load(Ordering::Relaxed)
+    }
+}
+
+// Estado de un hilo de búsqueda: la tabla compartida y las heurísticas propias
+pub struct SearchThread<'a> {
+    tt: &'a TranspositionTable,
+    // El hilo 0 es el principal: gestiona el tiempo e imprime la información
+    pub id: usize,
+    pub shared: Arc<SearchShared>,
+    pub ordering: Box<MoveOrdering>,
+    pub config: SearchConfig,
+    pub nodes: u64,
+    time: TimeManager,
+    // "go nodes N": se para exactamente al llegar a N nodos
+    max_nodes: Option<u64>,
+    // Se activa al agotar el tiempo; a partir de ahí los resultados no valen
+    stopped: bool,
+    // Jugada que llevó a cada ply (None tras un movimiento nulo)
+    move_stack: [Option<Move>; MAX_PLY as usize + 1],
+    // Tabla triangular de variantes principales, una por ply
+    pv: Vec<Vec<Move>>,
+    // Jugada que se excluye en la búsqueda de verificación de la extensión singular
+    excluded: [Option<Move>; MAX_PLY as usize + 1],
+    // Extensiones acumuladas en el camino hasta cada ply
+    extensions: [u32; MAX_PLY as usize + 1],
+    root_depth: u32,
+    // Jugadas de la raíz que ya tienen línea en esta iteración del MultiPV
+    root_excluded: Vec<Move>,
+    pawns: PawnTable,
+    // Acumuladores de la red por ply (solo si se evalúa con NNUE)
+    accumulators: Option<AccumulatorStack>,
+    // Piezas en el tablero en cada ply, reyes incluidos: dice sin recorrer el
+    // tablero si la posición puede estar en la bitbase o en las tablas
+    men: [usize; MAX_PLY as usize + 1],
+}
+
+impl<'a> SearchThread<'a> {
+    pub fn new(tt: &'a TranspositionTable) -> Self {
+        SearchThread {
+            tt,
+            id: 0,
+            shared: Arc::default(),
+            ordering: Box::default(),
+            config: SearchConfig::default(),
+            nodes: 0,
+            time: TimeManager::new(&SearchLimits::default(), Color::White),
+            max_nodes: None,
+            stopped: false,
+            move_stack: [None; MAX_PLY as usize + 1],
+            pv: vec![Vec::new(); MAX_PLY as usize + 2],
+            excluded: [None; MAX_PLY as usize + 1],
+            extensions: [0; MAX_PLY as usize + 1],
+            root_depth: 0,
+            root_excluded: Vec::new(),
+            pawns: PawnTable::new(),
+            accumulators: None,
+            men: [0; MAX_PLY as usize + 1],
+        }
+    }
+
+    pub fn search(&mut self, board: &Board, depth: u32) -> SearchResult {
+        self.search_with_limits(board, &SearchLimits::fixed_depth(depth))
+    }
+
+    // Profundización iterativa: buscamos a profundidad 1, 2, ... hasta agotar
+    // la profundidad o el tiempo. Cada iteración deja en la TT la mejor jugada
+    // para ordenar la siguiente.
+    pub fn search_with_limits(&mut self, board: &Board, limits: &SearchLimits) -> SearchResult {
+        self.nodes = 0;
+        self.stopped = false;
+        self.time = TimeManager::new(limits, board.turn);
+        self.max_nodes = limits.nodes;
+        self.refresh_accumulators(board);
+        self.men[0] = board.squares.iter().flatten().count();
+        let max_depth = limits.depth.unwrap_or(MAX_PLY as u32 - 1).max(1);
+        let multi_pv = self
+            .config
+            .multi_pv
+            .clamp(1, board.generate_moves().len().max(1));
+
+        let mut result = SearchResult {
+            best_move: None,
+            score: 0,
+            depth: 0,
+            nodes: 0,
+            pv: Vec::new(),
+            lines: Vec::new(),
+        };
+
+        // Si la raíz está en las tablas, la jugada sale de ellas sin buscar
+        if let Some(result) = self.tablebase_root(board, multi_pv) {
+            return self.finish_search(board, result);
+        }
+
+        for depth in 1..=max_depth {
+            // Los hilos auxiliares se saltan profundidades para no ir todos a la par
+            if self.id > 0 && depth > 1 && skip_depth(self.id, depth) {
+                continue;
+            }
+            self.root_depth = depth;
+
+            // MultiPV: cada línea se busca excluyendo en la raíz las jugadas de
+            // las anteriores, así cada una tiene su puntuación exacta
+            let mut lines = Vec::new();
+            self.root_excluded.clear();
+            for pv_index in 0..multi_pv {
+                let prev_score = result
+                    .lines
+                    .get(pv_index)
+                    .map_or(result.score, |line| line.score);
+                let score = self.aspiration_search(board, depth, prev_score);
+
+                if self.stopped && (result.best_move.is_some() || pv_index > 0) {
+                    break;
+                }
+                lines.push(PvLine {
+                    score,
+                    pv: self.pv[0].clone(),
+                });
+                match self.pv[0].first() {
+                    Some(mv) => self.root_excluded.push(*mv),
+                    None => break,
+                }
+            }
+            self.root_excluded.clear();
+
+            // Una iteración interrumpida no es fiable: nos quedamos con la anterior
+            if self.stopped && result.best_move.is_some() {
+                break;
+            }
+
+            // Con la inestabilidad de la búsqueda una línea posterior puede salir mejor
+            lines.sort_by_key(|line| -line.score);
+            result = SearchResult {
+                best_move: lines[0].pv.first().copied(),
+                score: lines[0].score,
+                depth,
+                nodes: self.nodes,
+                pv: lines[0].pv.clone(),
+                lines,
+            };
+
+            // Una primera iteración cortada no tiene nada que mostrar
+            if self.config.print_info && self.id == 0 && !self.stopped {
+                let nodes = self.shared.nodes().max(self.nodes);
+                for (i, line) in result.lines.iter().enumerate() {
+                    print_info(
+                        depth,
+                        i + 1,
+                        line,
+                        nodes,
+                        self.time.elapsed(),
+                        self.tt.hashfull(),
+                    );
+                }
+            }
+
+            // Sin jugadas legales no hay nada que profundizar. El límite
+            // blando de tiempo lo decide solo el hilo principal.
+            if result.best_move.is_none()
+                || self.stopped
+                || (self.id == 0 && !self.shared.is_pondering() && self.time.soft_limit_reached())
+            {
+                break;
+            }
+        }
+
+        self.finish_search(board, result)
+    }
+
+    fn finish_search(&mut self, board: &Board, mut result: SearchResult) -> SearchResult {
+        if self.id == 0 {
+            // La GUI no acepta la jugada mientras pensamos en su tiempo
+            while self.shared.is_pondering() && !self.shared.is_stopped() {
+                thread::sleep(Duration::from_millis(1));
+            }
+            // Al acabar el hilo principal se paran los auxiliares
+            self.shared.stop();
+        }
+
+        // Si nos quedamos sin tiempo antes de terminar la primera jugada
+        if result.best_move.is_none() {
+            result.best_move = board.generate_moves().first().copied();
+        }
+
+        result
+    }
+
+    // Las jugadas de la raíz ordenadas por las tablas, con la variante que
+    // sigue la mejor jugada de cada bando hasta el mate
+    fn tablebase_root(&self, board: &Board, multi_pv: usize) -> Option<SearchResult> {
+        let tablebases = self.config.tablebases.as_ref()?;
+        let lines: Vec<PvLine> = tablebases
+            .root_moves(board)?
+            .into_iter()
+            .take(multi_pv)
+            .map(|(mv, dtm)| {
+                let mut pv = vec![mv];
+                let mut position = board.clone();
+                position.make_move(&mv);
+                while dtm != Dtm::Draw && pv.len() < MAX_PLY as usize {
+                    let best = tablebases
+                        .root_moves(&position)
+                        .and_then(|moves| moves.first().map(|(mv, _)| *mv));
+                    let Some(best) = best else { break };
+                    pv.push(best);
+                    position.make_move(&best);
+                }
+                PvLine {
+                    score: tablebase_score(dtm, 0),
+                    pv,
+                }
+            })
+            .collect();
+        // Sin jugadas legales lo resuelve la búsqueda normal
+        let best = lines.first()?;
+
+        if self.config.print_info && self.id == 0 {
+            for (i, line) in lines.iter().enumerate() {
+                print_info(
+                    line.pv.len() as u32,
+                    i + 1,
+                    line,
+                    0,
+                    self.time.elapsed(),
+                    self.tt.hashfull(),
+                );
+            }
+        }
+        Some(SearchResult {
+            best_move: best.pv.first().copied(),
+            score: best.score,
+            depth: best.pv.len() as u32,
+            nodes: 0,
+            pv: best.pv.clone(),
+            lines: lines.clone(),
+        })
+    }
+
+    // Busca todas las jugadas de la raíz como líneas del MultiPV, así cada una
+    // recibe una puntuación exacta y no solo la cota que la descarta
+    pub fn analyze(&mut self, board: &Board, limits: &SearchLimits) -> Vec<RootMoveAnalysis> {
+        let multi_pv = self.config.multi_pv;
+        self.config.multi_pv = usize::MAX;
+        let result = self.search_with_limits(board, limits);
+        self.config.multi_pv = multi_pv;
+
+        result
+            .lines
+            .into_iter()
+            .filter_map(|line| {
+                Some(RootMoveAnalysis {
+                    mv: *line.pv.first()?,
+                    score: line.score,
+                    depth: result.depth,
+                    pv: line.pv,
+                })
+            })
+            .collect()
+    }
+
+    // Ventana de aspiración: buscamos con una ventana estrecha alrededor de la
+    // puntuación anterior y la ensanchamos si el resultado cae fuera.
+    fn aspiration_search(&mut self, board: &Board, depth: u32, prev_score: i32) -> i32 {
+        if !self.config.aspiration_windows
+            || depth < ASPIRATION_MIN_DEPTH
+            || prev_score.abs() >= MATE_BOUND
+        {
+            return self.negamax(board, depth, 0, -INFINITY, INFINITY);
+        }
+
+        let mut delta = ASPIRATION_WINDOW;
+        let mut alpha = (prev_score - delta).max(-INFINITY);
+        let mut beta = (prev_score + delta).min(INFINITY);
+
+        loop {
+            let score = self.negamax(board, depth, 0, alpha, beta);
+
+            if self.stopped {
+                return score;
+            }
+            if score <= alpha {
+                // Fail-low: bajamos alpha y acercamos beta para no perder resolución
+                beta = (alpha + beta) / 2;
+                alpha = (score - delta).max(-INFINITY);
+            } else if score >= beta {
+                beta = (score + delta).min(INFINITY);
+            } else {
+                return score;
+            }
+
+            delta += delta / 2;
+        }
+    }
+
+    fn negamax(&mut self, board: &Board, depth: u32, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+        self.pv[ply as usize].clear();
+        let pv_node = beta - alpha > 1;
+        let original_alpha = alpha;
+        let excluded = self.excluded[ply as usize];
+        let mut tt_move = None;
+
+        let tt_entry = self.tt.probe(board.hash, ply);
+        if let Some(entry) = tt_entry {
+            tt_move = entry.best_move;
+            // En los nodos PV siempre buscamos: necesitamos la jugada y su variante
+            // completa, que otro hilo puede haber dejado a medias en la tabla.
+            // Tampoco en la verificación singular, que busca otra cosa en la misma posición.
+            if !pv_node && excluded.is_none() && entry.depth >= depth {
+                match entry.bound {
+                    Bound::Exact => return entry.score,
+                    Bound::Lower if entry.score >= beta => return entry.score,
+                    Bound::Upper if entry.score <= alpha => return entry.score,
+                    _ => {}
+                }
+            }
+        }
+
+        // Las tablas de rey y peón contra rey se conocen sin buscar
+        let men = self.men[ply as usize];
+        if ply > 0 && men == 3 && probe_kpk(board) == Some(KpkResult::Draw) {
+            return 0;
+        }
+        // Con tablas de finales la posición tiene su resultado exacto
+        if ply > 0
+            && excluded.is_none()
+            && let Some(tablebases) = &self.config.tablebases
+            && men <= tablebases.max_men()
+            && let Some(dtm) = tablebases.probe(board)
+        {
+            return tablebase_score(dtm, ply);
+        }
+
+        // En las hojas seguimos con las capturas para no cortar a mitad de un intercambio
+        if depth == 0 || ply >= MAX_PLY {
+            return self.quiescence(board, ply, alpha, beta);
+        }
+
+        let moves = board.generate_moves();
+        let in_check = board.is_king_attacked(board.turn);
+        if moves.is_empty() {
+            if in_check {
+                return -MATE_SCORE + ply; // Un mate más cercano a la raíz vale más
+            } else {
+                return 0; // Tablas por ahogado
+            }
+        }
+
+        // En jaque la evaluación estática no significa nada
+        let eval = if in_check {
+            -INFINITY
+        } else {
+            self.static_eval(board, ply)
+        };
+
+        if !pv_node && !in_check && ply > 0 && excluded.is_none() {
+            // Poda de futilidad inversa
+            if self.config.reverse_futility
+                && depth <= RFP_MAX_DEPTH
+                && eval.abs() < MATE_BOUND
+                && eval - RFP_MARGIN * depth as i32 >= beta
+            {
+                return eval;
+            }
+
+            // Movimiento nulo: si incluso pasando el turno seguimos por encima de
+            // beta, el nodo casi seguro falla alto. No se usa sin piezas (zugzwang)
+            // ni dos veces seguidas.
+            if self.config.null_move
+                && depth >= NMP_MIN_DEPTH
+                && eval >= beta
+                && self.move_stack[ply as usize].is_some()
+                && board.has_non_pawn_material(board.turn)
+            {
+                let reduction = 3 + depth / 4;
+                let mut null_board = board.clone();
+                null_board.make_null_move();
+                self.push_null_accumulator(ply);
+                self.move_stack[ply as usize + 1] = None;
+                self.men[ply as usize + 1] = men;
+                let score = -self.negamax(
+                    &null_board,
+                    depth.saturating_sub(1 + reduction),
+                    ply + 1,
+                    -beta,
+                    -beta + 1,
+                );
+                if self.stopped {
+                    return 0;
+                }
+                if score >= beta {
+                    // Un mate encontrado tras pasar el turno no es real
+                    return if score >= MATE_BOUND { beta } else { score };
+                }
+            }
+        }
+
+        let futility_pruning = self.config.futility
+            && !pv_node
+            && !in_check
+            && depth <= FUTILITY_MAX_DEPTH
+            && eval + FUTILITY_BASE_MARGIN + FUTILITY_MARGIN * depth as i32 <= alpha;
+        let late_move_limit =
+            if self.config.late_move_pruning && !pv_node && !in_check && depth <= LMP_MAX_DEPTH {
+                Some(3 + (depth * depth) as usize)
+            } else {
+                None
+            };
+
+        // Extensión singular: si la jugada de la TT es mucho mejor que todas las
+        // demás (buscadas a menor profundidad sin ella), la extendemos
+        let mut singular_move = None;
+        if self.config.singular_extension
+            && ply > 0
+            && depth >= SINGULAR_MIN_DEPTH
+            && excluded.is_none()
+            && let Some(entry) = tt_entry
+            && let Some(tt_move) = entry.best_move
+            && entry.bound != Bound::Upper
+            && entry.depth + SINGULAR_TT_DEPTH_MARGIN >= depth
+            && entry.score.abs() < MATE_BOUND
+        {
+            let singular_beta = entry.score - 2 * depth as i32;
+            self.excluded[ply as usize] = Some(tt_move);
+            let score = self.negamax(
+                board,
+                (depth - 1) / 2,
+                ply,
+                singular_beta - 1,
+                singular_beta,
+            );
+            self.excluded[ply as usize] = None;
+            if self.stopped {
+                return 0;
+            }
+            if score < singular_beta {
+                singular_move = Some(tt_move);
+            }
+        }
+
+        let mut best_score = -INFINITY;
+        let mut best_move = None;
+        let mut tried_quiets = Vec::new();
+        let mut quiets_seen = 0;
+
+        // Jugadas que no se buscan en este nodo: la de la verificación singular y,
+        // en la raíz, las que ya ocupan una línea anterior del MultiPV
+        let mut skipped: Vec<Move> = excluded.into_iter().collect();
+        if ply == 0 {
+            skipped.extend_from_slice(&self.root_excluded);
+        }
+
+        for (i, mv) in self
+            .pick_moves(board, moves, tt_move, ply)
+            .filter(|mv| !skipped.contains(mv))
+            .enumerate()
+        {
+            let is_capture = board.is_capture(&mv);
+            let is_quiet = !is_capture && mv.promotion.is_none();
+            let mut new_board = board.clone();
+            new_board.make_move(&mv);
+            let gives_check = new_board.is_king_attacked(new_board.turn);
+
+            if is_quiet {
+                quiets_seen += 1;
+            }
+
+            // Podas de jugadas tranquilas. La primera jugada nunca se poda, así
+            // siempre tenemos una puntuación real.
+            if i > 0 && is_quiet && !gives_check && best_score > -MATE_BOUND {
+                if futility_pruning {
+                    continue;
+                }
+                if late_move_limit.is_some_and(|limit| quiets_seen > limit) {
+                    continue;
+                }
+            }
+
+            // Las jugadas podadas no llegan a necesitar el acumulador
+            self.push_accumulator(ply, board, &mv);
+            self.men[ply as usize + 1] = men - is_capture as usize;
+            let extension = self.extension(board, &new_board, &mv, gives_check, ply, singular_move);
+            self.extensions[ply as usize + 1] = self.extensions[ply as usize] + extension;
+
+            self.move_stack[ply as usize + 1] = Some(mv);
+            let new_depth = depth - 1 + extension;
+
+            let eval = if i == 0 {
+                -self.negamax(&new_board, new_depth, ply + 1, -beta, -alpha)
+            } else {
+                // LMR: las jugadas tranquilas tardías se buscan primero con menos
+                // profundidad, y solo si mejoran alpha se repiten completas
+                let mut reduction = 0;
+                if self.config.lmr
+                    && depth >= LMR_MIN_DEPTH
+                    && i >= LMR_MIN_MOVE
+                    && is_quiet
+                    && !in_check
+                    && !gives_check
+                    && extension == 0
+                {
+                    reduction = lmr_reduction(depth, i).saturating_sub(pv_node as u32);
+                    reduction = reduction.min(new_depth - 1);
+                }
+
+                // PVS: el resto de jugadas se prueban con ventana nula; solo si
+                // mejoran alpha se repite la búsqueda con la ventana completa
+                let (low, high) = if self.config.pvs {
+                    (-alpha - 1, -alpha)
+                } else {
+                    (-beta, -alpha)
+                };
+
+                let mut eval = -self.negamax(&new_board, new_depth - reduction, ply + 1, low, high);
+                if eval > alpha && reduction > 0 {
+                    eval = -self.negamax(&new_board, new_depth, ply + 1, low, high);
+                }
+                if self.config.pvs && eval > alpha && eval < beta {
+                    eval = -self.negamax(&new_board, new_depth, ply + 1, -beta, -alpha);
+                }
+                eval
+            };
+
+            if self.stopped {
+                return 0;
+            }
+
+            if eval > best_score {
+                best_score = eval;
+                best_move = Some(mv);
+            }
+            if eval > alpha {
+                alpha = eval;
+                self.update_pv(ply, mv);
+            }
+            if alpha >= beta {
+                if is_quiet {
+                    let prev_move = self.move_stack[ply as usize];
+                    self.ordering.update_quiet_cutoff(
+                        board,
+                        mv,
+                        &tried_quiets,
+                        depth,
+                        ply,
+                        prev_move,
+                    );
+                }
+                break;
+            }
+            if is_quiet {
+                tried_quiets.push(mv);
+            }
+        }
+
+        // Puede que todas las jugadas estuvieran excluidas
+        if best_score == -INFINITY {
+            return alpha;
+        }
+
+        let bound = if best_score >= beta {
+            Bound::Lower
+        } else if best_score > original_alpha {
+            Bound::Exact
         } else {
-            return 0; // Tablas por ahogado
+            // Si ninguna jugada superó alpha, la "mejor" no es fiable
+            best_move = None;
+            Bound::Upper
+        };
+        if skipped.is_empty() {
+            self.tt
+                .store(board.hash, depth, best_score, bound, best_move, ply);
+        }
+
+        best_score
+    }
+
+    // Ply de extensión para una jugada. Para que las líneas forzadas no hagan
+    // explotar el árbol, el total de extensiones de un camino no puede superar
+    // la profundidad nominal de la iteración.
+    fn extension(
+        &self,
+        board: &Board,
+        new_board: &Board,
+        mv: &Move,
+        gives_check: bool,
+        ply: i32,
+        singular_move: Option<Move>,
+    ) -> u32 {
+        if self.extensions[ply as usize] >= self.root_depth {
+            return 0;
         }
+
+        if singular_move == Some(*mv) {
+            return 1;
+        }
+
+        if self.config.check_extension && gives_check {
+            return 1;
+        }
+
+        if self.config.recapture_extension
+            && board.is_capture(mv)
+            && self.move_stack[ply as usize].is_some_and(|prev| prev.to == mv.to)
+        {
+            return 1;
+        }
+
+        if self.config.passed_pawn_extension
+            && let Some(piece) = new_board.squares[mv.to]
+            && piece.piece_type == PieceType::Pawn
+        {
+            // Peón pasado que llega a la séptima (segunda para las negras)
+            if relative_rank(mv.to, piece.color) == 6
+                && PawnStructure::new(new_board).is_passed(mv.to, piece.color)
+            {
+                return 1;
+            }
+        }
+
+        0
     }
 
-    if depth == 0 {
-        let score = evaluate(board);
-        return if board.turn == Color::White {
+    // Evaluación estática desde el punto de vista del bando que mueve
+    fn static_eval(&mut self, board: &Board, ply: i32) -> i32 {
+        if let (Some(net), Some(stack)) = (&self.config.nnue, &self.accumulators) {
+            // La red no sabe de finales conocidos
+            let scale = match endgame::verdict(board) {
+                Verdict::Score(score) if board.turn == Color::White => return score,
+                Verdict::Score(score) => return -score,
+                Verdict::Scale(scale) => scale,
+            };
+            return net.output(stack.get(ply as usize), board.turn) / scale;
+        }
+        let score = evaluate_cached(board, &mut self.pawns);
+        if board.turn == Color::White {
             score
         } else {
             -score
+        }
+    }
+
+    // Acumuladores de la raíz calculados desde cero
+    fn refresh_accumulators(&mut self, board: &Board) {
+        let Some(net) = &self.config.nnue else {
+            self.accumulators = None;
+            return;
+        };
+        if self
+            .accumulators
+            .as_ref()
+            .is_none_or(|stack| stack.hidden() != net.hidden)
+        {
+            self.accumulators = Some(AccumulatorStack::new(net.hidden, MAX_PLY as usize + 2));
+        }
+        if let Some(stack) = &mut self.accumulators {
+            stack.refresh(net, board);
+        }
+    }
+
+    // Acumuladores del ply siguiente tras jugar `mv` en `board`
+    fn push_accumulator(&mut self, ply: i32, board: &Board, mv: &Move) {
+        if let (Some(net), Some(stack)) = (&self.config.nnue, &mut self.accumulators) {
+            stack.push(ply as usize, net, board, mv);
+        }
+    }
+
+    fn push_null_accumulator(&mut self, ply: i32) {
+        if let Some(stack) = &mut self.accumulators {
+            stack.push_null(ply as usize);
+        }
+    }
+
+    fn should_stop(&mut self) -> bool {
+        // El límite de nodos se mira en cada nodo para que la búsqueda sea reproducible
+        if self
+            .max_nodes
+            .is_some_and(|max_nodes| self.nodes >= max_nodes)
+        {
+            self.stopped = true;
+        }
+        if !self.stopped && self.nodes.is_multiple_of(TIME_CHECK_INTERVAL) {
+            if self.nodes > 0 {
+                self.shared
+                    .nodes
+                    .fetch_add(TIME_CHECK_INTERVAL, Ordering::Relaxed);
+            }
+            self.stopped = self.shared.is_stopped()
+                || (!self.shared.is_pondering() && self.time.hard_limit_reached());
+        }
+        self.stopped
+    }
+
+    // La variante principal de este ply es la jugada seguida de la del siguiente
+    fn update_pv(&mut self, ply: i32, mv: Move) {
+        let ply = ply as usize;
+        let (head, tail) = self.pv.split_at_mut(ply + 1);
+        head[ply].clear();
+        head[ply].push(mv);
+        head[ply].extend_from_slice(&tail[0]);
+    }
+
+    // Búsqueda de quietud: solo capturas y promociones (o todas las evasiones si
+    // estamos en jaque) hasta llegar a una posición tranquila que evaluar.
+    fn quiescence(&mut self, board: &Board, ply: i32, mut alpha: i32, beta: i32) -> i32 {
+        if self.should_stop() {
+            return 0;
+        }
+        self.nodes += 1;
+        let moves = board.generate_moves();
+        let in_check = board.is_king_attacked(board.turn);
+
+        if moves.is_empty() {
+            return if in_check { -MATE_SCORE + ply } else { 0 };
+        }
+
+        let stand_pat = self.static_eval(board, ply);
+        if ply >= MAX_PLY {
+            return stand_pat;
+        }
+
+        let mut best_score = -INFINITY;
+        let picker = if in_check {
+            // En jaque no podemos "quedarnos quietos": hay que mirar todas las evasiones
+            self.pick_moves(board, moves, None, ply)
+        } else {
+            // Stand pat: el bando que mueve puede no capturar nada
+            if stand_pat >= beta {
+                return stand_pat;
+            }
+            alpha = alpha.max(stand_pat);
+            best_score = stand_pat;
+
+            let captures = moves
+                .into_iter()
+                .filter(|mv| {
+                    if mv.promotion.is_some() {
+                        return true;
+                    }
+                    let Some(captured) = board.captured_piece(mv) else {
+                        return false;
+                    };
+                    // Poda delta: ni ganando la pieza entera llegamos a alpha
+                    if stand_pat + piece_value(captured) + DELTA_MARGIN < alpha {
+                        return false;
+                    }
+                    // Las capturas que pierden material no cambian el resultado
+                    see(board, mv) >= 0
+                })
+                .collect();
+            MovePicker::captures(board, captures)
         };
+
+        for mv in picker {
+            let mut new_board = board.clone();
+            new_board.make_move(&mv);
+            self.push_accumulator(ply, board, &mv);
+            let score = -self.quiescence(&new_board, ply + 1, -beta, -alpha);
+            if self.stopped {
+                return 0;
+            }
+
+            if score > best_score {
+                best_score = score;
+            }
+            alpha = alpha.max(score);
+            if alpha >= beta {
+                break;
+            }
+        }
+
+        best_score
+    }
+
+    fn pick_moves(
+        &self,
+        board: &Board,
+        moves: Vec<Move>,
+        tt_move: Option<Move>,
+        ply: i32,
+    ) -> MovePicker {
+        if !self.config.move_ordering {
+            return MovePicker::unordered(moves);
+        }
+        let prev_move = self.move_stack[ply as usize];
+        MovePicker::new(board, moves, tt_move, &self.ordering, ply, prev_move)
     }
+}
 
-    for mv in moves {
-        let mut new_board = board.clone();
-        new_board.make_move(&mv);
-        let eval = -negamax(&new_board, depth - 1, -beta, -alpha);
-        if eval >= beta {
-            return beta;
+// Reducción de LMR según la profundidad y el número de jugada (crece con el
+// logaritmo de ambos)
+fn lmr_reduction(depth: u32, move_index: usize) -> u32 {
+    static TABLE: OnceLock<[[u32; 64]; 64]> = OnceLock::new();
+    let table = TABLE.get_or_init(|| {
+        let mut table = [[0; 64]; 64];
+        for (depth, row) in table.iter_mut().enumerate().skip(1) {
+            for (move_index, reduction) in row.iter_mut().enumerate().skip(1) {
+                let value = 0.75 + (depth as f64).ln() * (move_index as f64).ln() / 2.25;
+                *reduction = value as u32;
+            }
         }
-        alpha = alpha.max(eval);
+        table
+    });
+    table[(depth as usize).min(63)][move_index.min(63)]
+}
+
+// Lazy SMP: cada hilo auxiliar se salta una de cada SKIP_SIZE[i] profundidades
+// (con desfase SKIP_PHASE[i]) para que los hilos exploren árboles distintos
+const SKIP_SIZE: [u32; 20] = [1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 3, 3, 4, 4, 4, 4, 4, 4, 4, 4];
+const SKIP_PHASE: [u32; 20] = [0, 1, 0, 1, 2, 3, 0, 1, 2, 3, 4, 5, 0, 1, 2, 3, 4, 5, 6, 7];
+
+fn skip_depth(thread_id: usize, depth: u32) -> bool {
+    let i = (thread_id - 1) % SKIP_SIZE.len();
+    !((depth + SKIP_PHASE[i]) / SKIP_SIZE[i]).is_multiple_of(2)
+}
+
+fn print_info(
+    depth: u32,
+    multipv: usize,
+    line: &PvLine,
+    nodes: u64,
+    elapsed: Duration,
+    hashfull: usize,
+) {
+    let millis = elapsed.as_millis().max(1) as u64;
+    let pv: Vec<String> = line.pv.iter().map(|mv| mv.to_string()).collect();
+    println!(
+        "info depth {} multipv {} score {} nodes {} nps {} time {} hashfull {} pv {}",
+        depth,
+        multipv,
+        format_score(line.score),
+        nodes,
+        nodes * 1000 / millis,
+        millis,
+        hashfull,
+        pv.join(" ")
+    );
+}
+
+// Puntuación de un resultado de las tablas a `ply` medias jugadas de la raíz,
+// en la misma escala que los mates de la búsqueda. Un mate que queda más allá
+// de MAX_PLY se queda en el umbral: sigue siendo un mate, aunque más largo.
+pub fn tablebase_score(dtm: Dtm, ply: i32) -> i32 {
+    match dtm {
+        Dtm::Win(plies) => (MATE_SCORE - ply - plies as i32).max(MATE_BOUND),
+        Dtm::Loss(plies) => (-MATE_SCORE + ply + plies as i32).min(-MATE_BOUND),
+        Dtm::Draw => 0,
     }
+}
 
-    alpha
+// Formatea una puntuación para UCI: "cp <centipeones>" o "mate <jugadas>"
+pub fn format_score(score: i32) -> String {
+    if score >= MATE_BOUND {
+        format!("mate {}", (MATE_SCORE - score + 1) / 2)
+    } else if score <= -MATE_BOUND {
+        format!("mate -{}", (MATE_SCORE + score) / 2)
+    } else {
+        format!("cp {}", score)
+    }
 }
@@ -0,0 +1,66 @@
+use crate::{
+    evaluation::piece_value,
+    types::{Board, Move, PieceType},
+};
+
+fn see_value(piece_type: PieceType) -> i32 {
+    match piece_type {
+        // El rey solo puede capturar el último, nunca se "pierde"
+        PieceType::King => 20000,
+        _ => piece_value(piece_type),
+    }
+}
+
+// Static Exchange Evaluation: material que gana (o pierde) el bando que
+// mueve si ambos bandos capturan en la casilla destino con su pieza menos
+// valiosa mientras les convenga.
+pub fn see(board: &Board, mv: &Move) -> i32 {
+    let Some(moving) = board.squares[mv.from] else {
+        return 0;
+    };
+
+    let mut gain = [0i32; 32];
+    gain[0] = board.captured_piece(mv).map_or(0, see_value);
+
+    // La pieza que queda en la casilla es la que se arriesga en la siguiente captura
+    let mut at_risk = match mv.promotion {
+        Some(promotion) => {
+            gain[0] += see_value(promotion) - see_value(PieceType::Pawn);
+            see_value(promotion)
+        }
+        None => see_value(moving.piece_type),
+    };
+
+    let mut removed: u64 = 1 << mv.from;
+    let mut side = moving.color.opposite();
+    let mut depth = 0;
+
+    loop {
+        let attackers = board.attackers_of(mv.to, side, removed);
+        let Some(&attacker) = attackers
+            .iter()
+            .min_by_key(|&&square| see_value(board.squares[square].unwrap().piece_type))
+        else {
+            break;
+        };
+
+        depth += 1;
+        gain[depth] = at_risk - gain[depth - 1];
+
+        // Si ni capturando ni dejando de capturar cambia el signo, no hace falta seguir
+        if (-gain[depth - 1]).max(gain[depth]) < 0 || depth == gain.len() - 1 {
+            break;
+        }
+
+        at_risk = see_value(board.squares[attacker].unwrap().piece_type);
+        removed |= 1 << attacker;
+        side = side.opposite();
+    }
+
+    while depth > 0 {
+        gain[depth - 1] = -(-gain[depth - 1]).max(gain[depth]);
+        depth -= 1;
+    }
+
+    gain[0]
+}
@@ -0,0 +1,120 @@
+use crate::{search::PvLine, timeman::SearchLimits, types::Move};
+
+pub const MAX_SKILL_LEVEL: u32 = 20;
+// Rango de UCI_Elo: 800 corresponde al nivel 0 y 2400 al nivel máximo
+pub const MIN_ELO: u32 = 800;
+pub const MAX_ELO: u32 = 2400;
+
+// Candidatas entre las que elige un nivel reducido (se buscan con MultiPV)
+pub const SKILL_MULTI_PV: usize = 4;
+
+// Generador pseudoaleatorio xorshift64*: con la misma semilla, las mismas jugadas
+pub struct Rng(u64);
+
+impl Rng {
+    pub fn new(seed: u64) -> Self {
+        // xorshift no admite el estado 0
+        Rng(seed.max(1))
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 ^= self.0 >> 12;
+        self.0 ^= self.0 << 25;
+        self.0 ^= self.0 >> 27;
+        self.0.wrapping_mul(0x2545_F491_4F6C_DD1D)
+    }
+
+    // Número en [0, n)
+    pub fn below(&mut self, n: u64) -> u64 {
+        self.next_u64() % n.max(1)
+    }
+}
+
+// Limitación de fuerza. Un nivel bajo busca menos, elige entre las mejores
+// candidatas con ruido proporcional a su debilidad y a veces juega a propósito
+// una jugada algo peor.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Skill {
+    pub level: u32,
+}
+
+impl Skill {
+    pub fn from_level(level: u32) -> Self {
+        Skill {
+            level: level.min(MAX_SKILL_LEVEL),
+        }
+    }
+
+    pub fn from_elo(elo: u32) -> Self {
+        let elo = elo.clamp(MIN_ELO, MAX_ELO);
+        let level =
+            ((elo - MIN_ELO) * MAX_SKILL_LEVEL + (MAX_ELO - MIN_ELO) / 2) / (MAX_ELO - MIN_ELO);
+        Self::from_level(level)
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.level < MAX_SKILL_LEVEL
+    }
+
+    // Profundidad máxima: 1 en el nivel 0, 10 en el nivel 19
+    pub fn max_depth(&self) -> u32 {
+        1 + self.level / 2
+    }
+
+    pub fn max_nodes(&self) -> u64 {
+        let level = self.level as u64 + 1;
+        level * level * 1000
+    }
+
+    pub fn limit(&self, limits: &mut SearchLimits) {
+        if !self.enabled() {
+            return;
+        }
+        limits.depth = Some(
+            limits
+                .depth
+                .map_or(self.max_depth(), |depth| depth.min(self.max_depth())),
+        );
+        limits.nodes = Some(
+            limits
+                .nodes
+                .map_or(self.max_nodes(), |nodes| nodes.min(self.max_nodes())),
+        );
+    }
+
+    // Elige entre las líneas del MultiPV (ordenadas de mejor a peor). A cada
+    // puntuación se le suma un ruido de hasta `spread` centipeones: cuanto más
+    // lejos está una jugada de la mejor, menos probable es que la adelante.
+    pub fn pick_move(&self, lines: &[PvLine], rng: &mut Rng) -> Option<Move> {
+        let candidates: Vec<&PvLine> = lines.iter().filter(|line| !line.pv.is_empty()).collect();
+        if !self.enabled() || candidates.len() < 2 {
+            return candidates.first().map(|line| line.pv[0]);
+        }
+
+        let weakness = (MAX_SKILL_LEVEL - self.level) as u64;
+
+        // Imprecisión deliberada: hasta un 20% de las veces en el nivel 0, y solo
+        // entre las jugadas que pierden como mucho `weakness * 20` centipeones
+        if rng.below(100) < weakness {
+            let margin = weakness as i32 * 20;
+            let inaccuracies: Vec<&&PvLine> = candidates[1..]
+                .iter()
+                .filter(|line| candidates[0].score - line.score <= margin)
+                .collect();
+            if !inaccuracies.is_empty() {
+                let index = rng.below(inaccuracies.len() as u64) as usize;
+                return Some(inaccuracies[index].pv[0]);
+            }
+        }
+
+        let spread = weakness * 15 + 1;
+        candidates
+            .iter()
+            .map(|line| (line.pv[0], line.score + rng.below(spread) as i32))
+            .fold(None, |best: Option<(Move, i32)>, (mv, value)| match best {
+                Some((_, best_value)) if best_value >= value => best,
+                _ => Some((mv, value)),
+            })
+            .map(|(mv, _)| mv)
+    }
+}
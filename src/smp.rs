@@ -0,0 +1,105 @@
+use crate::{
+    search::{SearchConfig, SearchResult, SearchShared, SearchThread},
+    timeman::SearchLimits,
+    tt::TranspositionTable,
+    types::{Board, Move},
+};
+use std::{sync::Arc, thread};
+
+// La pila por defecto de los hilos (2 MB) se queda corta para la recursión de
+// la búsqueda, que copia el tablero en cada ply
+pub const THREAD_STACK_SIZE: usize = 8 * 1024 * 1024;
+
+// Lazy SMP: todos los hilos buscan la misma posición compartiendo solo la
+// tabla de transposición. Cada uno tiene su copia del tablero y sus propias
+// heurísticas de ordenación; al final se elige la jugada por votación.
+// Con un solo hilo el resultado es el mismo que el de SearchThread.
+pub fn search_parallel(
+    board: &Board,
+    limits: &SearchLimits,
+    tt: &TranspositionTable,
+    threads: usize,
+    config: SearchConfig,
+    shared: Arc<SearchShared>,
+) -> SearchResult {
+    tt.new_search();
+
+    let results: Vec<SearchResult> = thread::scope(|scope| {
+        let handles: Vec<_> = (1..threads.max(1))
+            .map(|id| {
+                let shared = shared.clone();
+                let board = board.clone();
+                let config = config.clone();
+                thread::Builder::new()
+                    .stack_size(THREAD_STACK_SIZE)
+                    .spawn_scoped(scope, move || {
+                        let mut thread = SearchThread::new(tt);
+                        thread.id = id;
+                        thread.shared = shared;
+                        thread.config = config;
+                        thread.search_with_limits(&board, limits)
+                    })
+                    .expect("No se pudo crear el hilo de búsqueda")
+            })
+            .collect();
+
+        // El hilo principal busca en el hilo actual
+        let mut main_thread = SearchThread::new(tt);
+        main_thread.shared = shared.clone();
+        main_thread.config = config.clone();
+        let mut results = vec![main_thread.search_with_limits(board, limits)];
+
+        results.extend(
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("Un hilo de búsqueda falló")),
+        );
+        results
+    });
+
+    let total_nodes = results.iter().map(|result| result.nodes).sum();
+    // Con MultiPV las líneas del hilo principal son las que se han mostrado
+    let mut best = if config.multi_pv > 1 {
+        results.into_iter().next().unwrap()
+    } else {
+        select_best_thread(results)
+    };
+    best.nodes = total_nodes;
+    best
+}
+
+// Votación entre hilos: cada uno vota por su jugada con un peso que crece con
+// la profundidad alcanzada y con lo buena que es su puntuación frente a la
+// peor. Gana el hilo más profundo de entre los que votan a la jugada ganadora.
+fn select_best_thread(results: Vec<SearchResult>) -> SearchResult {
+    let min_score = results.iter().map(|result| result.score).min().unwrap_or(0);
+    let weight =
+        |result: &SearchResult| (result.score - min_score + 14) as i64 * result.depth as i64;
+
+    let mut votes: Vec<(Move, i64)> = Vec::new();
+    for result in results.iter().filter(|result| result.depth > 0) {
+        let Some(mv) = result.best_move else {
+            continue;
+        };
+        match votes.iter_mut().find(|(voted, _)| *voted == mv) {
+            Some((_, total)) => *total += weight(result),
+            None => votes.push((mv, weight(result))),
+        }
+    }
+
+    let mut best_index = 0;
+    for (i, result) in results.iter().enumerate().skip(1) {
+        let best = &results[best_index];
+        let votes_for = |result: &SearchResult| {
+            votes
+                .iter()
+                .find(|(mv, _)| Some(*mv) == result.best_move)
+                .map_or(0, |(_, total)| *total)
+        };
+        if (votes_for(result), result.depth) > (votes_for(best), best.depth) {
+            best_index = i;
+        }
+    }
+
+    results.into_iter().nth(best_index).unwrap()
+}
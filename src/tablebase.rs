@@ -0,0 +1,866 @@
+use crate::{
+    endgame::MaterialKey,
+    evaluation::piece_value,
+    types::{Board, CastlingRights, Color, Move, Piece, PieceType, Square},
+};
+use std::{collections::HashMap, fmt, fs, path::Path, thread, time::Instant};
+
+// Tablas de finales con distancia al mate (DTM) de hasta cuatro piezas
+// contando los reyes. Se generan por análisis retrógrado con el propio
+// generador de jugadas del tablero y se guardan en un archivo por material.
+pub const MAX_MEN: usize = 4;
+pub const EXTENSION: &str = "cbtb";
+
+// Formato en disco: "CBTB", versión (u32), longitud de la firma (u8), la firma
+// ("KQKR") y el número de posiciones (u32), seguidos de un byte por posición
+// en el que las series de ceros (tablas y posiciones imposibles, la mayoría)
+// se guardan como un 0 y su longitud en LEB128
+const MAGIC: &[u8; 4] = b"CBTB";
+const VERSION: u32 = 1;
+
+// Orden de las piezas de cada bando en el índice, después de los reyes
+const PIECE_ORDER: [PieceType; 5] = [
+    PieceType::Queen,
+    PieceType::Rook,
+    PieceType::Bishop,
+    PieceType::Knight,
+    PieceType::Pawn,
+];
+
+// Sin peones el tablero tiene 8 simetrías: el rey blanco siempre en a1-d1-d4
+const TRIANGLE: [Square; 10] = [0, 1, 2, 3, 9, 10, 11, 18, 19, 27];
+
+// Resultado para el bando que mueve, con las medias jugadas hasta el mate
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Dtm {
+    Win(u8),
+    Loss(u8),
+    Draw,
+}
+
+impl Dtm {
+    // 0 son tablas; si no, las medias jugadas hasta el mate más uno. Con un
+    // número par de medias jugadas el que mueve recibe el mate.
+    fn from_byte(byte: u8) -> Dtm {
+        match byte {
+            0 => Dtm::Draw,
+            byte if (byte - 1) % 2 == 0 => Dtm::Loss(byte - 1),
+            byte => Dtm::Win(byte - 1),
+        }
+    }
+
+    fn to_byte(self) -> u8 {
+        match self {
+            Dtm::Draw => 0,
+            Dtm::Win(plies) | Dtm::Loss(plies) => plies + 1,
+        }
+    }
+
+    // El resultado de la posición anterior, visto por el bando que movió
+    fn parent(self) -> Dtm {
+        match self {
+            Dtm::Win(plies) => Dtm::Loss(plies + 1),
+            Dtm::Loss(plies) => Dtm::Win(plies + 1),
+            Dtm::Draw => Dtm::Draw,
+        }
+    }
+
+    // Para ordenar de mejor a peor: ganar cuanto antes, perder cuanto más tarde
+    fn rank(self) -> i32 {
+        match self {
+            Dtm::Win(plies) => 1000 - plies as i32,
+            Dtm::Draw => 0,
+            Dtm::Loss(plies) => -1000 + plies as i32,
+        }
+    }
+
+    // Jugadas hasta el mate, en positivo si gana el que mueve
+    pub fn mate_in(self) -> Option<i32> {
+        match self {
+            Dtm::Win(plies) => Some((plies as i32 + 1) / 2),
+            Dtm::Loss(plies) => Some(-(plies as i32) / 2),
+            Dtm::Draw => None,
+        }
+    }
+}
+
+// Firma en la orientación en que se guarda: el bando más fuerte con blancas.
+// Devuelve también si hay que voltear la posición para consultarla.
+fn canonical_key(key: MaterialKey) -> (MaterialKey, bool) {
+    let strength = |color| {
+        PIECE_ORDER
+            .iter()
+            .map(|&piece_type| key.count(color, piece_type) as i32 * piece_value(piece_type))
+            .sum::<i32>()
+    };
+    let flipped = key.flip();
+    let (white, black) = (strength(Color::White), strength(Color::Black));
+    if black > white || (black == white && flipped.code() > key.code()) {
+        (flipped, true)
+    } else {
+        (key, false)
+    }
+}
+
+fn men(key: &MaterialKey) -> usize {
+    2 + [Color::White, Color::Black]
+        .iter()
+        .flat_map(|&color| {
+            PIECE_ORDER
+                .iter()
+                .map(move |&pt| key.count(color, pt) as usize)
+        })
+        .sum::<usize>()
+}
+
+// Materiales a los que se puede llegar con una captura o una coronación
+fn dependencies(key: &MaterialKey) -> Vec<MaterialKey> {
+    let code = key.code();
+    let split = code[1..].find('K').map_or(code.len(), |i| i + 1);
+    let sides = [code[..split].to_string(), code[split..].to_string()];
+    let mut codes = Vec::new();
+
+    for side in 0..2 {
+        for (i, c) in sides[side].char_indices().skip(1) {
+            let mut captured = sides.clone();
+            captured[side].remove(i);
+            codes.push(captured.concat());
+            if c != 'P' {
+                continue;
+            }
+            for promotion in ["Q", "R", "B", "N"] {
+                let mut promoted = sides.clone();
+                promoted[side].replace_range(i..i + 1, promotion);
+                codes.push(promoted.concat());
+                // Coronación comiendo
+                for (j, _) in sides[1 - side].char_indices().skip(1) {
+                    let mut both = promoted.clone();
+                    both[1 - side].remove(j);
+                    codes.push(both.concat());
+                }
+            }
+        }
+    }
+    codes
+        .iter()
+        .filter_map(|code| MaterialKey::from_code(code))
+        .map(|key| canonical_key(key).0)
+        .collect()
+}
+
+// Todas las firmas de hasta MAX_MEN piezas, de menos a más material
+pub fn all_codes() -> Vec<String> {
+    let pieces = ["Q", "R", "B", "N", "P"];
+    let mut sets = vec![String::new()];
+    for (i, a) in pieces.iter().enumerate() {
+        sets.push(a.to_string());
+        for b in &pieces[i..] {
+            sets.push(format!("{}{}", a, b));
+        }
+    }
+
+    let mut codes = Vec::new();
+    for white in &sets {
+        for black in &sets {
+            if white.len() + black.len() + 2 > MAX_MEN {
+                continue;
+            }
+            let key = MaterialKey::from_code(&format!("K{}K{}", white, black)).unwrap();
+            let code = canonical_key(key).0.code();
+            if !codes.contains(&code) {
+                codes.push(code);
+            }
+        }
+    }
+    codes.sort_by_key(|code| code.len());
+    codes
+}
+
+pub struct Table {
+    pub key: MaterialKey,
+    // Las piezas en el orden del índice: los dos reyes y luego las demás
+    pieces: Vec<Piece>,
+    pawns: bool,
+    data: Vec<u8>,
+}
+
+impl Table {
+    fn new(key: MaterialKey) -> Self {
+        let mut pieces = vec![
+            Piece::new(Color::White, PieceType::King),
+            Piece::new(Color::Black, PieceType::King),
+        ];
+        for color in [Color::White, Color::Black] {
+            for piece_type in PIECE_ORDER {
+                for _ in 0..key.count(color, piece_type) {
+                    pieces.push(Piece::new(color, piece_type));
+                }
+            }
+        }
+        let pawns = pieces.iter().any(|p| p.piece_type == PieceType::Pawn);
+        Table {
+            key,
+            pieces,
+            pawns,
+            data: Vec::new(),
+        }
+    }
+
+    fn king_slots(&self) -> usize {
+        if self.pawns { 32 } else { TRIANGLE.len() }
+    }
+
+    // Dos bandos, las casillas posibles del rey blanco y 64 para cada pieza más
+    pub fn size(&self) -> usize {
+        2 * self.king_slots() * 64usize.pow(self.pieces.len() as u32 - 1)
+    }
+
+    fn encode(&self, turn: Color, squares: &[Square]) -> usize {
+        let king = squares[0];
+        let slot = if self.pawns {
+            king / 8 * 4 + king % 8
+        } else {
+            TRIANGLE.iter().position(|&sq| sq == king).unwrap()
+        };
+        let mut index = turn as usize * self.king_slots() + slot;
+        for &square in &squares[1..] {
+            index = index * 64 + square;
+        }
+        index
+    }
+
+    fn decode(&self, mut index: usize) -> (Color, Vec<Square>) {
+        let mut squares = vec![0; self.pieces.len()];
+        for square in squares[1..].iter_mut().rev() {
+            *square = index % 64;
+            index /= 64;
+        }
+        let slot = index % self.king_slots();
+        squares[0] = if self.pawns {
+            slot / 4 * 8 + slot % 4
+        } else {
+            TRIANGLE[slot]
+        };
+        let turn = if index / self.king_slots() == 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        (turn, squares)
+    }
+
+    // Las piezas iguales se guardan de menor a mayor casilla
+    fn sort_identical(&self, squares: &mut [Square]) {
+        let mut start = 0;
+        while start < squares.len() {
+            let end = (start..squares.len())
+                .find(|&i| self.pieces[i] != self.pieces[start])
+                .unwrap_or(squares.len());
+            squares[start..end].sort_unstable();
+            start = end;
+        }
+    }
+
+    // Índice de la posición después de llevar el rey blanco a su zona
+    fn canonical_index(&self, turn: Color, mut squares: Vec<Square>) -> usize {
+        let transform = |squares: &mut [Square], f: fn(Square) -> Square| {
+            squares.iter_mut().for_each(|square| *square = f(*square))
+        };
+        if squares[0] % 8 >= 4 {
+            transform(&mut squares, |sq| sq ^ 7);
+        }
+        if !self.pawns {
+            if squares[0] / 8 >= 4 {
+                transform(&mut squares, |sq| sq ^ 56);
+            }
+            if squares[0] / 8 > squares[0] % 8 {
+                transform(&mut squares, transpose);
+            }
+            // Con el rey en la diagonal las dos orientaciones valen: la menor
+            if squares[0] / 8 == squares[0] % 8 {
+                let mut transposed = squares.clone();
+                transform(&mut transposed, transpose);
+                self.sort_identical(&mut squares);
+                self.sort_identical(&mut transposed);
+                return self
+                    .encode(turn, &squares)
+                    .min(self.encode(turn, &transposed));
+            }
+        }
+        self.sort_identical(&mut squares);
+        self.encode(turn, &squares)
+    }
+
+    // Índice de una posición con el material de la tabla
+    pub fn index(&self, board: &Board) -> Option<usize> {
+        let mut squares = vec![None; self.pieces.len()];
+        for (square, piece) in board.squares.iter().enumerate() {
+            let Some(piece) = piece else { continue };
+            let slot = (0..self.pieces.len())
+                .find(|&i| self.pieces[i] == *piece && squares[i].is_none())?;
+            squares[slot] = Some(square);
+        }
+        let squares: Option<Vec<Square>> = squares.into_iter().collect();
+        Some(self.canonical_index(board.turn, squares?))
+    }
+
+    // La posición de un índice, si es legal y es la forma canónica
+    fn position(&self, index: usize, empty: &Board) -> Option<Board> {
+        let (turn, squares) = self.decode(index);
+        for (i, &square) in squares.iter().enumerate() {
+            if squares[..i].contains(&square)
+                || (self.pieces[i].piece_type == PieceType::Pawn && !(8..56).contains(&square))
+            {
+                return None;
+            }
+        }
+        if self.canonical_index(turn, squares.clone()) != index {
+            return None;
+        }
+
+        let mut board = empty.clone();
+        for (piece, square) in self.pieces.iter().zip(squares) {
+            board.squares[square] = Some(*piece);
+        }
+        board.turn = turn;
+        // El bando que no mueve no puede estar en jaque
+        (!board.is_king_attacked(turn.opposite())).then_some(board)
+    }
+
+    // Posiciones de la misma tabla desde las que se llega a esta con una
+    // jugada: se deshace un movimiento del bando que acaba de mover. Con
+    // `skip_en_passant` se omiten los avances dobles a los que se puede
+    // responder comiendo al paso: la generación los trata como salidas.
+    fn predecessors(&self, board: &Board, skip_en_passant: bool) -> Vec<usize> {
+        let mover = board.turn.opposite();
+        let occupied = board
+            .squares
+            .iter()
+            .enumerate()
+            .filter(|(_, piece)| piece.is_some())
+            .fold(0u64, |bits, (square, _)| bits | 1 << square);
+        let mut result = Vec::new();
+
+        for (from, piece) in board.squares.iter().enumerate() {
+            let Some(piece) = piece.filter(|piece| piece.color == mover) else {
+                continue;
+            };
+            let targets = if piece.piece_type == PieceType::Pawn {
+                pawn_unpushes(from, mover, occupied)
+            } else {
+                board.piece_attacks(from) & !occupied
+            };
+            for to in (0..64).filter(|to| targets >> to & 1 == 1) {
+                if skip_en_passant && piece.piece_type == PieceType::Pawn && from.abs_diff(to) == 16
+                {
+                    let mut pushed = board.clone();
+                    pushed.en_passant_target = Some((from + to) / 2);
+                    if !en_passant_captures(&pushed).is_empty() {
+                        continue;
+                    }
+                }
+                let mut previous = board.clone();
+                previous.squares[to] = previous.squares[from].take();
+                previous.turn = mover;
+                if !previous.is_king_attacked(board.turn) {
+                    result.extend(self.index(&previous));
+                }
+            }
+        }
+        result.sort_unstable();
+        result.dedup();
+        result
+    }
+
+    // Resultado guardado en un índice; las posiciones imposibles son tablas
+    pub fn dtm_at(&self, index: usize) -> Dtm {
+        Dtm::from_byte(self.data[index])
+    }
+
+    pub fn get(&self, board: &Board) -> Option<Dtm> {
+        self.index(board)
+            .map(|index| Dtm::from_byte(self.data[index]))
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let code = self.key.code();
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(MAGIC);
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.push(code.len() as u8);
+        bytes.extend_from_slice(code.as_bytes());
+        bytes.extend_from_slice(&(self.data.len() as u32).to_le_bytes());
+
+        let mut i = 0;
+        while i < self.data.len() {
+            if self.data[i] != 0 {
+                bytes.push(self.data[i]);
+                i += 1;
+                continue;
+            }
+            let run = self.data[i..].iter().take_while(|&&b| b == 0).count();
+            bytes.push(0);
+            let mut rest = run;
+            loop {
+                let low = (rest & 0x7f) as u8;
+                rest >>= 7;
+                if rest == 0 {
+                    bytes.push(low);
+                    break;
+                }
+                bytes.push(low | 0x80);
+            }
+            i += run;
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Table, String> {
+        if bytes.len() < 9 || &bytes[..4] != MAGIC {
+            return Err("Tabla inválida: no es un archivo CBTB".to_string());
+        }
+        let version = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+        if version != VERSION {
+            return Err(format!("Tabla inválida: versión {} no soportada", version));
+        }
+        let code_end = 9 + bytes[8] as usize;
+        let code = bytes
+            .get(9..code_end)
+            .and_then(|code| std::str::from_utf8(code).ok())
+            .ok_or("Tabla inválida: firma truncada")?;
+        let key = MaterialKey::from_code(code)
+            .filter(|key| men(key) <= MAX_MEN && canonical_key(*key).0 == *key)
+            .ok_or(format!("Tabla inválida: firma '{}'", code))?;
+        let mut table = Table::new(key);
+        let size = bytes
+            .get(code_end..code_end + 4)
+            .map(|size| u32::from_le_bytes(size.try_into().unwrap()) as usize)
+            .ok_or("Tabla inválida: falta el tamaño")?;
+        if size != table.size() {
+            return Err(format!("Tabla inválida: {} posiciones en {}", size, code));
+        }
+
+        let mut data = Vec::with_capacity(size);
+        let mut rest = bytes[code_end + 4..].iter();
+        while let Some(&byte) = rest.next() {
+            if byte != 0 {
+                data.push(byte);
+                continue;
+            }
+            let (mut run, mut shift) = (0usize, 0);
+            loop {
+                let low = *rest.next().ok_or("Tabla inválida: serie truncada")?;
+                run |= ((low & 0x7f) as usize) << shift;
+                shift += 7;
+                if low & 0x80 == 0 {
+                    break;
+                }
+            }
+            data.resize(data.len() + run, 0);
+        }
+        if data.len() != size {
+            return Err(format!("Tabla inválida: datos de {} truncados", code));
+        }
+        table.data = data;
+        Ok(table)
+    }
+}
+
+// Capturas al paso legales del bando que mueve
+fn en_passant_captures(board: &Board) -> Vec<Move> {
+    let Some(target) = board.en_passant_target else {
+        return Vec::new();
+    };
+    board
+        .generate_moves()
+        .into_iter()
+        .filter(|mv| {
+            mv.to == target
+                && board.squares[mv.from].is_some_and(|p| p.piece_type == PieceType::Pawn)
+        })
+        .collect()
+}
+
+fn transpose(square: Square) -> Square {
+    square % 8 * 8 + square / 8
+}
+
+// Casillas desde las que un peón pudo avanzar hasta `square`
+fn pawn_unpushes(square: Square, color: Color, occupied: u64) -> u64 {
+    let (back, start_rank, double_rank): (isize, usize, usize) = match color {
+        Color::White => (-8, 1, 3),
+        Color::Black => (8, 6, 4),
+    };
+    let one = (square as isize + back) as Square;
+    if occupied >> one & 1 == 1 {
+        return 0;
+    }
+    let mut targets = 0;
+    // Un peón no puede haber estado en la primera fila
+    if (1..7).contains(&(one / 8)) {
+        targets |= 1 << one;
+    }
+    let two = (one as isize + back) as Square;
+    if square / 8 == double_rank && two / 8 == start_rank && occupied >> two & 1 == 0 {
+        targets |= 1 << two;
+    }
+    targets
+}
+
+// Estado de una posición durante la generación
+#[derive(Debug, Clone, Copy, Default)]
+struct Node {
+    valid: bool,
+    done: bool,
+    // Sucesoras de la misma tabla que aún no se sabe que ganan
+    remaining: u8,
+    // Medias jugadas de la derrota más larga entre las sucesoras ya resueltas
+    worst: u8,
+    // Medias jugadas de la victoria más corta saliendo de la tabla (0 si no hay)
+    exit_win: u8,
+    // Alguna captura o coronación lleva a tablas
+    draw_exit: bool,
+}
+
+#[derive(Default)]
+pub struct Tablebases {
+    tables: HashMap<MaterialKey, Table>,
+    // Piezas de la tabla más grande: con más no hace falta ni mirar
+    max_men: usize,
+}
+
+// Solo las firmas: los datos son millones de bytes
+impl fmt::Debug for Tablebases {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut codes: Vec<String> = self.tables.keys().map(MaterialKey::code).collect();
+        codes.sort();
+        f.debug_struct("Tablebases")
+            .field("tables", &codes)
+            .finish()
+    }
+}
+
+impl Tablebases {
+    pub fn new() -> Self {
+        Tablebases::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.tables.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.tables.is_empty()
+    }
+
+    pub fn max_men(&self) -> usize {
+        self.max_men
+    }
+
+    fn insert(&mut self, table: Table) {
+        self.max_men = self.max_men.max(men(&table.key));
+        self.tables.insert(table.key, table);
+    }
+
+    pub fn table(&self, code: &str) -> Option<&Table> {
+        let key = MaterialKey::from_code(code)?;
+        self.tables.get(&canonical_key(key).0)
+    }
+
+    // Carga todas las tablas de un directorio
+    pub fn load(dir: &str) -> Result<Tablebases, String> {
+        let entries = fs::read_dir(dir).map_err(|e| format!("No se pudo leer {}: {}", dir, e))?;
+        let mut tablebases = Tablebases::new();
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == EXTENSION) {
+                let bytes = fs::read(&path)
+                    .map_err(|e| format!("No se pudo leer {}: {}", path.display(), e))?;
+                let table = Table::from_bytes(&bytes)?;
+                tablebases.insert(table);
+            }
+        }
+        Ok(tablebases)
+    }
+
+    pub fn save(&self, dir: &str) -> Result<(), String> {
+        fs::create_dir_all(dir).map_err(|e| format!("No se pudo crear {}: {}", dir, e))?;
+        for (key, table) in &self.tables {
+            let path = Path::new(dir).join(format!("{}.{}", key.code(), EXTENSION));
+            fs::write(&path, table.to_bytes())
+                .map_err(|e| format!("No se pudo escribir {}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+
+    // Resultado de la posición para el bando que mueve, si hay tabla. Las
+    // tablas no tienen enroques; la captura al paso se resuelve aparte,
+    // mirando el resultado de cada una en la tabla más pequeña. Cada material
+    // se guarda en una sola orientación: si no está tal cual, está con los
+    // colores cambiados y se consulta la posición volteada.
+    pub fn probe(&self, board: &Board) -> Option<Dtm> {
+        if board.castling_rights != CastlingRights::default() {
+            return None;
+        }
+        if board.en_passant_target.is_some() {
+            let mut plain = board.clone();
+            plain.en_passant_target = None;
+            let dtm = self.probe(&plain)?;
+            let captures = self.en_passant_results(board)?;
+            return captures
+                .into_iter()
+                .chain([dtm])
+                .max_by_key(|dtm| dtm.rank());
+        }
+        let key = MaterialKey::new(board);
+        if let Some(table) = self.tables.get(&key) {
+            return table.get(board);
+        }
+        self.tables.get(&key.flip())?.get(&board.mirror())
+    }
+
+    // Resultados de las capturas al paso legales para el bando que mueve
+    // (vacío si no hay ninguna), o None si falta la tabla de alguna
+    fn en_passant_results(&self, board: &Board) -> Option<Vec<Dtm>> {
+        en_passant_captures(board)
+            .into_iter()
+            .map(|mv| {
+                let mut next = board.clone();
+                next.make_move(&mv);
+                self.probe(&next).map(Dtm::parent)
+            })
+            .collect()
+    }
+
+    // Las jugadas legales con su resultado, de mejor a peor
+    pub fn root_moves(&self, board: &Board) -> Option<Vec<(Move, Dtm)>> {
+        let mut moves = Vec::new();
+        for mv in board.generate_moves() {
+            let mut next = board.clone();
+            next.make_move(&mv);
+            moves.push((mv, self.probe(&next)?.parent()));
+        }
+        moves.sort_by_key(|(_, dtm)| -dtm.rank());
+        Some(moves)
+    }
+
+    // Genera la tabla de un material y las que necesita, si no están ya
+    pub fn generate(&mut self, code: &str) -> Result<(), String> {
+        let key = MaterialKey::from_code(code).ok_or(format!("Firma inválida: '{}'", code))?;
+        let (key, _) = canonical_key(key);
+        if men(&key) > MAX_MEN {
+            return Err(format!("{} tiene más de {} piezas", code, MAX_MEN));
+        }
+        if self.tables.contains_key(&key) {
+            return Ok(());
+        }
+        for dependency in dependencies(&key) {
+            self.generate(&dependency.code())?;
+        }
+        let table = self.build(key);
+        self.insert(table);
+        Ok(())
+    }
+
+    // Primero se mira cada posición hacia delante (mates, ahogados y lo que
+    // dan las capturas y coronaciones según las tablas ya hechas). Después se
+    // resuelven por orden de distancia al mate deshaciendo jugadas: quien
+    // puede llevar al rival a una derrota gana en una media jugada más, y
+    // quien solo tiene jugadas que dejan ganar al rival pierde.
+    //
+    // Tras un avance doble al que el rival puede responder comiendo al paso,
+    // la sucesora vale lo mejor entre su resultado en la tabla y el de comer
+    // al paso. Su resultado en la tabla sale de la pasada anterior, así que
+    // con peones de los dos bandos se repite hasta que la tabla no cambia. El
+    // avance doble no se deshace: las dependencias no forman ciclos y cada
+    // pasada fija las posiciones con un avance doble más por delante.
+    fn build(&self, key: MaterialKey) -> Table {
+        let mut table = self.build_pass(key, None);
+        if key.count(Color::White, PieceType::Pawn) == 0
+            || key.count(Color::Black, PieceType::Pawn) == 0
+        {
+            return table;
+        }
+        loop {
+            let next = self.build_pass(key, Some(&table));
+            if next.data == table.data {
+                return next;
+            }
+            table = next;
+        }
+    }
+
+    fn build_pass(&self, key: MaterialKey, last_pass: Option<&Table>) -> Table {
+        let mut table = Table::new(key);
+        let size = table.size();
+        let empty = Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").unwrap();
+
+        let mut nodes = vec![Node::default(); size];
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        let chunk = size.div_ceil(threads);
+        thread::scope(|scope| {
+            for (i, nodes) in nodes.chunks_mut(chunk).enumerate() {
+                let (table, empty) = (&table, &empty);
+                scope.spawn(move || {
+                    for (offset, node) in nodes.iter_mut().enumerate() {
+                        *node = self.forward(table, last_pass, i * chunk + offset, empty);
+                    }
+                });
+            }
+        });
+
+        // buckets[n]: posiciones que se resuelven con n medias jugadas al mate
+        let mut buckets: Vec<Vec<u32>> = vec![Vec::new()];
+        fn push(buckets: &mut Vec<Vec<u32>>, plies: u8, index: usize) {
+            if buckets.len() <= plies as usize {
+                buckets.resize(plies as usize + 1, Vec::new());
+            }
+            buckets[plies as usize].push(index as u32);
+        }
+        for (index, node) in nodes.iter().enumerate() {
+            if !node.valid {
+                continue;
+            }
+            if node.done {
+                // Mate en el tablero
+                push(&mut buckets, 0, index);
+            } else if node.exit_win > 0 {
+                push(&mut buckets, node.exit_win, index);
+            } else if node.remaining == 0 && !node.draw_exit {
+                push(&mut buckets, node.worst, index);
+            }
+        }
+        for node in nodes.iter_mut() {
+            node.done = false;
+        }
+
+        table.data = vec![0; size];
+        let mut plies = 0;
+        while plies < buckets.len() {
+            let bucket = std::mem::take(&mut buckets[plies]);
+            for index in bucket {
+                let index = index as usize;
+                if nodes[index].done {
+                    continue;
+                }
+                nodes[index].done = true;
+                let dtm = if plies % 2 == 0 {
+                    Dtm::Loss(plies as u8)
+                } else {
+                    Dtm::Win(plies as u8)
+                };
+                table.data[index] = dtm.to_byte();
+
+                let board = table.position(index, &empty).unwrap();
+                for previous in table.predecessors(&board, last_pass.is_some()) {
+                    let node = &mut nodes[previous];
+                    if node.done || !node.valid {
+                        continue;
+                    }
+                    let next = plies as u8 + 1;
+                    if plies % 2 == 0 {
+                        push(&mut buckets, next, previous);
+                    } else {
+                        node.remaining -= 1;
+                        node.worst = node.worst.max(next);
+                        if node.remaining == 0 && node.exit_win == 0 && !node.draw_exit {
+                            let worst = node.worst;
+                            push(&mut buckets, worst, previous);
+                        }
+                    }
+                }
+            }
+            plies += 1;
+        }
+        // Lo que no se ha resuelto son tablas
+        table
+    }
+
+    fn forward(
+        &self,
+        table: &Table,
+        last_pass: Option<&Table>,
+        index: usize,
+        empty: &Board,
+    ) -> Node {
+        let Some(board) = table.position(index, empty) else {
+            return Node::default();
+        };
+        let mut node = Node {
+            valid: true,
+            ..Node::default()
+        };
+        let moves = board.generate_moves();
+        if moves.is_empty() {
+            // Mate o ahogado: el ahogado queda como tablas sin resolver
+            node.done = board.is_king_attacked(board.turn);
+            node.draw_exit = !node.done;
+            return node;
+        }
+
+        let mut successors = Vec::with_capacity(moves.len());
+        for mv in &moves {
+            let mut next = board.clone();
+            next.make_move(mv);
+            let exit = if board.is_capture(mv) || mv.promotion.is_some() {
+                self.probe(&next)
+            } else {
+                let captures = self
+                    .en_passant_results(&next)
+                    .expect("Falta una tabla de la que depende");
+                match last_pass {
+                    Some(last_pass) if !captures.is_empty() => {
+                        let dtm = last_pass.get(&next).expect("Sucesora fuera de la tabla");
+                        captures
+                            .into_iter()
+                            .chain([dtm])
+                            .max_by_key(|dtm| dtm.rank())
+                    }
+                    _ => {
+                        successors.push(table.index(&next).expect("Sucesora fuera de la tabla"));
+                        continue;
+                    }
+                }
+            };
+            match exit.expect("Falta una tabla de la que depende") {
+                Dtm::Loss(plies) => {
+                    let win = plies + 1;
+                    if node.exit_win == 0 || win < node.exit_win {
+                        node.exit_win = win;
+                    }
+                }
+                Dtm::Win(plies) => node.worst = node.worst.max(plies + 1),
+                Dtm::Draw => node.draw_exit = true,
+            }
+        }
+        successors.sort_unstable();
+        successors.dedup();
+        node.remaining = successors.len() as u8;
+        node
+    }
+}
+
+// "chess-bot-gm tablebase <directorio> [firmas...]": genera las tablas pedidas
+// (sin firmas, todas las de hasta MAX_MEN piezas) y las guarda en el directorio
+pub fn run_generate(dir: &str, codes: &[&str]) -> Result<(), String> {
+    let start = Instant::now();
+    let codes = if codes.is_empty() {
+        all_codes()
+    } else {
+        codes.iter().map(|code| code.to_string()).collect()
+    };
+    let mut tablebases = Tablebases::new();
+    for code in &codes {
+        let before = tablebases.len();
+        tablebases.generate(code)?;
+        println!(
+            "{}: {} tablas nuevas ({} ms)",
+            code,
+            tablebases.len() - before,
+            start.elapsed().as_millis()
+        );
+    }
+    tablebases.save(dir)?;
+    println!("{} tablas escritas en {}", tablebases.len(), dir);
+    Ok(())
+}
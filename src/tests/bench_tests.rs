@@ -0,0 +1,25 @@
+use crate::{
+    bench::{BENCH_FENS, bench_signature},
+    types::{Board, Color},
+};
+
+#[test]
+fn test_bench_signature_is_deterministic() {
+    let signature = bench_signature(4);
+    assert!(signature > 0);
+    assert_eq!(signature, bench_signature(4));
+    assert!(bench_signature(5) > signature);
+}
+
+#[test]
+fn test_bench_positions_are_legal() {
+    for fen in BENCH_FENS {
+        let board = Board::from_fen(fen).unwrap_or_else(|e| panic!("{}: {}", fen, e));
+        // El bando que no mueve nunca puede estar en jaque
+        let waiting = match board.turn {
+            Color::White => Color::Black,
+            Color::Black => Color::White,
+        };
+        assert!(!board.is_king_attacked(waiting), "Posición ilegal: {}", fen);
+    }
+}
@@ -5,13 +5,13 @@ use crate::types::*;
 // Convierte notación algebraica ("a1", "h8") a índice (0..63)
 // Esto hace que los tests sean mucho más fáciles de leer.
 fn square(square: &str) -> usize {
-    let col = square.chars().nth(0).unwrap() as usize - 'a' as usize;
+    let col = square.chars().next().unwrap() as usize - 'a' as usize;
     let row = square.chars().nth(1).unwrap() as usize - '1' as usize;
     row * 8 + col
 }
 
 // Verifica si un movimiento existe en la lista generada
-fn contains_move(moves: &Vec<Move>, from: &str, to: &str) -> bool {
+fn contains_move(moves: &[Move], from: &str, to: &str) -> bool {
     let f = square(from);
     let t = square(to);
     moves.iter().any(|m| m.from == f && m.to == t)
@@ -894,3 +894,84 @@ fn test_to_fen_empty_squares() {
     let board = Board::from_fen(fen_empty).unwrap();
     assert_eq!(board.to_fen(), fen_empty);
 }
+
+// --- TESTS DE HASH ZOBRIST ---
+
+#[test]
+fn test_hash_incremental_matches_full() {
+    // Recorremos varias posiciones con enroques, al paso y promociones
+    let fens = [
+        "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+        "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+        "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+    ];
+
+    for fen in fens {
+        let board = Board::from_fen(fen).unwrap();
+        for mv in board.generate_moves() {
+            let mut child = board.clone();
+            child.make_move(&mv);
+            assert_eq!(
+                child.hash,
+                crate::zobrist::compute_hash(&child),
+                "Hash incremental incorrecto tras {} en {}",
+                mv,
+                fen
+            );
+            assert_eq!(
+                child.pawn_hash,
+                crate::zobrist::compute_pawn_hash(&child),
+                "Hash de peones incorrecto tras {} en {}",
+                mv,
+                fen
+            );
+        }
+    }
+}
+
+#[test]
+fn test_pawn_hash_ignores_pieces() {
+    // Mover piezas no cambia la estructura de peones; mover un peón sí
+    let board = Board::initial_position();
+    let mut knight = board.clone();
+    knight.make_move(&knight.parse_move("g1f3").unwrap());
+    assert_eq!(knight.pawn_hash, board.pawn_hash);
+    assert_ne!(knight.hash, board.hash);
+
+    let mut pawn = board.clone();
+    pawn.make_move(&pawn.parse_move("e2e4").unwrap());
+    assert_ne!(pawn.pawn_hash, board.pawn_hash);
+}
+
+#[test]
+fn test_hash_transposition() {
+    // Nf3 Nf6 Nc3 y Nc3 Nf6 Nf3 llegan a la misma posición
+    let mut a = Board::initial_position();
+    for mv in ["g1f3", "g8f6", "b1c3"] {
+        let m = a.parse_move(mv).unwrap();
+        a.make_move(&m);
+    }
+    let mut b = Board::initial_position();
+    for mv in ["b1c3", "g8f6", "g1f3"] {
+        let m = b.parse_move(mv).unwrap();
+        b.make_move(&m);
+    }
+
+    assert_eq!(a.hash, b.hash);
+    assert_ne!(a.hash, Board::initial_position().hash);
+}
+
+#[test]
+fn test_piece_attacks() {
+    let board = Board::from_fen("4k3/8/8/3p4/4N3/8/1P6/R3K3 w - - 0 1").unwrap();
+    // Caballo en el centro: 8 casillas
+    assert_eq!(board.piece_attacks(28).count_ones(), 8);
+    // Torre de a1: columna a entera y b1..e1, parándose en el rey propio
+    assert_eq!(board.piece_attacks(0).count_ones(), 7 + 4);
+    assert_ne!(board.piece_attacks(0) & (1 << 4), 0);
+    // El peón de b2 ataca a3 y c3; el de d5 ataca c4 y e4
+    assert_eq!(board.piece_attacks(9), (1 << 16) | (1 << 18));
+    assert_eq!(board.piece_attacks(35), (1 << 26) | (1 << 28));
+    // Casilla vacía
+    assert_eq!(board.piece_attacks(20), 0);
+}
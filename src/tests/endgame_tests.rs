@@ -0,0 +1,178 @@
+use crate::{
+    endgame::{DRAWISH_SCALE, Endgame, KNOWN_WIN, MaterialKey, Verdict, evaluate, probe, verdict},
+    evaluation,
+    search::search,
+    tt::TranspositionTable,
+    types::{Board, Color},
+};
+
+fn endgame_eval(fen: &str) -> Option<i32> {
+    evaluate(&Board::from_fen(fen).expect("FEN inválido"))
+}
+
+// Juega la posición contra sí misma hasta el mate o hasta `max_moves` jugadas
+// del bando fuerte. Devuelve cuántas hicieron falta.
+fn moves_to_mate(fen: &str, depth: u32, max_moves: usize) -> Option<usize> {
+    let mut board = Board::from_fen(fen).expect("FEN inválido");
+    let strong = board.turn;
+    let tt = TranspositionTable::new(4);
+    for ply in 0..max_moves * 2 {
+        let moves = board.generate_moves();
+        if moves.is_empty() {
+            let mated = board.is_king_attacked(board.turn) && board.turn != strong;
+            return mated.then_some(ply.div_ceil(2));
+        }
+        let best = search(&board, depth, &tt).best_move?;
+        board.make_move(&best);
+    }
+    None
+}
+
+#[test]
+fn test_material_key_codes() {
+    let key = MaterialKey::from_code("KBNK").unwrap();
+    assert_eq!(key.code(), "KBNK");
+    assert_eq!(key.flip().code(), "KKBN");
+    assert_eq!(MaterialKey::from_code("KRPKR").unwrap().code(), "KRPKR");
+
+    let board = Board::from_fen("8/8/3k4/8/8/2BN4/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(MaterialKey::new(&board), key);
+    assert_eq!(MaterialKey::new(&board.mirror()), key.flip());
+
+    assert!(MaterialKey::from_code("").is_none());
+    assert!(MaterialKey::from_code("KQ").is_none());
+    assert!(MaterialKey::from_code("KXK").is_none());
+}
+
+#[test]
+fn test_insufficient_material_is_draw() {
+    for fen in [
+        "8/8/3k4/8/8/8/8/4K3 w - - 0 1",
+        "8/8/3k4/8/8/2N5/8/4K3 w - - 0 1",
+        "8/8/3k4/8/8/2b5/8/4K3 w - - 0 1",
+        "8/8/3k4/8/8/2NN4/8/4K3 b - - 0 1",
+        "8/8/3k1n2/8/8/2B5/8/4K3 w - - 0 1",
+    ] {
+        assert_eq!(endgame_eval(fen), Some(0), "{}", fen);
+    }
+}
+
+#[test]
+fn test_rook_against_rook_is_drawish() {
+    // No es tablas seguras: la evaluación normal se reduce, pero no se anula
+    let board = Board::from_fen("8/8/3k4/3r4/8/2R5/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(probe(&board), Some((Endgame::Drawish, Color::White)));
+    assert_eq!(evaluate(&board), None);
+
+    let trace = evaluation::trace(&board);
+    let normal = trace.total().taper(trace.phase);
+    assert_eq!(evaluation::evaluate(&board), normal / DRAWISH_SCALE);
+    assert_eq!(trace.score(), normal / DRAWISH_SCALE);
+
+    // Dama contra dama con las dos colgando: quien mueve se lleva la otra
+    let board = Board::from_fen("3k4/8/8/3q4/8/8/8/3QK3 w - - 0 1").unwrap();
+    assert_eq!(probe(&board), Some((Endgame::Drawish, Color::White)));
+    let tt = TranspositionTable::new(1);
+    let result = search(&board, 2, &tt);
+    assert_eq!(
+        result.best_move.map(|mv| mv.to_string()),
+        Some("d1d5".into())
+    );
+    assert!(result.score > KNOWN_WIN);
+}
+
+#[test]
+fn test_verdict_is_score_or_scale() {
+    let verdict_of = |fen| verdict(&Board::from_fen(fen).expect("FEN inválido"));
+    assert_eq!(
+        verdict_of("8/8/3k4/3r4/8/2R5/8/4K3 w - - 0 1"),
+        Verdict::Scale(DRAWISH_SCALE)
+    );
+    assert!(matches!(
+        verdict_of("8/8/3k4/8/8/2Q5/8/4K3 w - - 0 1"),
+        Verdict::Score(score) if score > KNOWN_WIN
+    ));
+    // Con piezas de más en los dos bandos ni se construye la firma
+    let board = Board::from_fen("8/8/3k4/3r4/8/2RN4/8/4K3 w - - 0 1").unwrap();
+    assert_eq!(probe(&board), None);
+    assert_eq!(verdict(&board), Verdict::Scale(1));
+    assert_eq!(verdict(&Board::initial_position()), Verdict::Scale(1));
+}
+
+#[test]
+fn test_wrong_bishop_and_rook_pawn() {
+    // Alfil de casillas oscuras: no controla a8 y el rey negro está en la esquina
+    assert_eq!(endgame_eval("k7/8/8/P7/8/8/3B4/4K3 w - - 0 1"), Some(0));
+    assert_eq!(endgame_eval("8/1k6/8/P7/P7/8/3B4/4K3 w - - 0 1"), Some(0));
+    // Con el alfil bueno se gana
+    assert_eq!(endgame_eval("k7/8/8/P7/8/8/4B3/4K3 w - - 0 1"), None);
+    // Con el rey lejos de la esquina tampoco es tablas seguras
+    assert_eq!(endgame_eval("8/8/8/P3k3/8/8/3B4/4K3 w - - 0 1"), None);
+    // Ni con peones en otra columna
+    assert_eq!(endgame_eval("k7/8/8/PP6/8/8/3B4/4K3 w - - 0 1"), None);
+    // Lo mismo con colores cambiados: peón de h negro, alfil de oscuras y h1
+    assert_eq!(endgame_eval("4k3/8/8/8/7p/8/3b4/7K b - - 0 1"), Some(0));
+}
+
+#[test]
+fn test_lone_king_is_known_win() {
+    let white = endgame_eval("8/8/3k4/8/8/8/8/3QK3 w - - 0 1").unwrap();
+    assert!(white > KNOWN_WIN);
+    let black = endgame_eval("3qk3/8/8/8/8/8/3K4/8 w - - 0 1").unwrap();
+    assert!(black < -KNOWN_WIN);
+    // Dos alfiles del mismo color no bastan; de colores distintos, sí
+    assert!(endgame_eval("8/8/3k4/8/8/8/8/2B1KB2 w - - 0 1").unwrap() > KNOWN_WIN);
+    assert_eq!(endgame_eval("8/8/3k4/8/8/8/8/1B2KB2 w - - 0 1"), None);
+    // Con peones del bando débil no es un final conocido
+    assert_eq!(endgame_eval("8/8/3k4/3p4/8/8/8/3QK3 w - - 0 1"), None);
+}
+
+#[test]
+fn test_lone_king_pushed_to_edge() {
+    // El rey fuerte siempre a dos casillas
+    let center = endgame_eval("8/7R/8/4k3/8/2K5/8/8 w - - 0 1").unwrap();
+    let edge = endgame_eval("8/7R/8/8/8/2K5/8/4k3 w - - 0 1").unwrap();
+    let corner = endgame_eval("8/7R/8/8/8/2K5/8/k7 w - - 0 1").unwrap();
+    assert!(center < edge && edge < corner);
+
+    // Con el rey fuerte cerca, mejor
+    let far = endgame_eval("3k4/8/8/8/8/8/8/R3K3 w - - 0 1").unwrap();
+    let close = endgame_eval("3k4/8/3K4/8/8/8/8/R7 w - - 0 1").unwrap();
+    assert!(close > far);
+}
+
+#[test]
+fn test_bishop_and_knight_corner() {
+    // Alfil de casillas claras: el mate es en h1 o a8, no en a1 o h8
+    let right = endgame_eval("k7/8/8/8/8/8/8/3BNK2 w - - 0 1").unwrap();
+    let wrong = endgame_eval("7k/8/8/8/8/8/8/3BNK2 w - - 0 1").unwrap();
+    assert!(right > wrong, "{} <= {}", right, wrong);
+    assert!(wrong > KNOWN_WIN);
+}
+
+#[test]
+fn test_queen_mates_lone_king() {
+    let moves = moves_to_mate("8/8/8/3k4/8/8/8/3QK3 w - - 0 1", 5, 15);
+    assert!(moves.is_some(), "KQK sin mate en 15 jugadas");
+}
+
+#[test]
+fn test_rook_mates_lone_king() {
+    let moves = moves_to_mate("8/8/8/3k4/8/8/8/R3K3 w - - 0 1", 5, 30);
+    assert!(moves.is_some(), "KRK sin mate en 30 jugadas");
+}
+
+#[test]
+fn test_bishop_and_knight_mate() {
+    // Sin detección de repeticiones hace falta algo de profundidad para no
+    // dar vueltas mientras se lleva al rey a la esquina buena
+    for fen in [
+        // El rey negro empieza en la esquina equivocada
+        "7k/8/8/8/8/8/8/3BNK2 w - - 0 1",
+        "8/8/8/4k3/8/8/8/2B1NK2 w - - 0 1",
+        "8/8/3k4/8/8/8/8/1N2K1B1 w - - 0 1",
+    ] {
+        let moves = moves_to_mate(fen, 8, 50);
+        assert!(moves.is_some(), "{}: KBNK sin mate en 50 jugadas", fen);
+    }
+}
@@ -0,0 +1,86 @@
+use crate::{
+    king_safety::{attack_units, king_safety, king_zone, pawn_cover},
+    params::SAFETY_TABLE,
+    types::{Board, Color},
+};
+
+// Cobertura de peones del rey blanco en g1
+fn white_cover(fen: &str) -> i32 {
+    let board = Board::from_fen(fen).expect("FEN inválido");
+    pawn_cover(&board, 6, Color::White).mg
+}
+
+#[test]
+fn test_intact_shield_beats_advanced_pawns() {
+    let intact = white_cover("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1");
+    let pushed = white_cover("6k1/5ppp/8/8/6P1/8/5P1P/6K1 w - - 0 1");
+    assert!(intact > pushed);
+}
+
+#[test]
+fn test_open_files_near_king() {
+    let intact = white_cover("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1");
+    // Sin peón blanco en g: semiabierta (queda g7) o abierta del todo
+    let semi_open = white_cover("6k1/5ppp/8/8/8/8/5P1P/6K1 w - - 0 1");
+    let open = white_cover("6k1/5p1p/8/8/8/8/5P1P/6K1 w - - 0 1");
+    assert!(intact > semi_open);
+    assert!(semi_open > open);
+}
+
+#[test]
+fn test_pawn_storm() {
+    let far = white_cover("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1");
+    let storm = white_cover("6k1/5p1p/8/8/8/6p1/5PPP/6K1 w - - 0 1");
+    // El peón de g3 ataca y además deja g semiabierta para las negras, no
+    // para las blancas: la diferencia es solo la tormenta
+    assert!(storm < far);
+}
+
+#[test]
+fn test_king_zone() {
+    // Rey en g1: filas 1 a 3 y columnas f a h
+    assert_eq!(king_zone(6, Color::White).count_ones(), 9);
+    // Rey en e4: cuatro filas (una atrás, dos adelante) de tres columnas
+    assert_eq!(king_zone(28, Color::White).count_ones(), 12);
+    // Para las negras la zona se extiende hacia abajo: g6 sí, g5 no
+    assert_ne!(king_zone(62, Color::Black) & (1 << 46), 0);
+    assert_eq!(king_zone(62, Color::Black) & (1 << 38), 0);
+}
+
+#[test]
+fn test_attack_units_need_several_attackers() {
+    // Solo la dama ataca la zona del rey: sin penalización
+    let queen = Board::from_fen("6k1/8/8/8/8/6q1/5PPP/6K1 w - - 0 1").unwrap();
+    // Dama y caballo juntos
+    let queen_knight = Board::from_fen("6k1/8/8/8/5n2/6q1/5PPP/6K1 w - - 0 1").unwrap();
+
+    let (attackers, units) = attack_units(&queen_knight, 6, Color::White);
+    assert_eq!(attackers, 2);
+    assert!(units > 0);
+    assert_eq!(attack_units(&queen, 6, Color::White).0, 1);
+
+    let alone = king_safety(&queen).mg - pawn_cover(&queen, 6, Color::White).mg;
+    let together = king_safety(&queen_knight).mg - pawn_cover(&queen_knight, 6, Color::White).mg;
+    assert!(together < alone);
+}
+
+#[test]
+fn test_king_safety_only_in_middlegame() {
+    let board = Board::from_fen("6k1/5p1p/8/8/5n2/6q1/5P1P/6K1 w - - 0 1").unwrap();
+    let score = king_safety(&board);
+    assert!(score.mg < 0);
+    assert_eq!(score.eg, 0);
+}
+
+#[test]
+fn test_king_safety_symmetry() {
+    let board =
+        Board::from_fen("r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2NB1N2/PP3PPP/R2Q1RK1 w - - 0 1").unwrap();
+    assert_eq!(king_safety(&board), -king_safety(&board.mirror()));
+}
+
+#[test]
+fn test_safety_table_is_monotonic() {
+    // Más unidades de ataque, más penalización
+    assert!(SAFETY_TABLE.windows(2).all(|w| w[0] >= w[1]));
+}
@@ -0,0 +1,188 @@
+use crate::{
+    endgame::{self, KNOWN_WIN},
+    kpk::{KpkResult, generate, probe_kpk},
+    search::search,
+    tt::TranspositionTable,
+    types::{Board, Color, Piece, PieceType, Square},
+};
+
+fn probe(fen: &str) -> Option<KpkResult> {
+    probe_kpk(&Board::from_fen(fen).expect("FEN inválido"))
+}
+
+// La misma posición con los colores cambiados y reflejada en horizontal
+fn flipped(board: &Board) -> Board {
+    let mut flipped = board.mirror();
+    let squares = flipped.squares;
+    for (square, piece) in squares.iter().enumerate() {
+        flipped.squares[square ^ 7] = *piece;
+    }
+    flipped
+}
+
+fn kpk_board(turn: Color, white_king: Square, black_king: Square, pawn: Square) -> Board {
+    let mut board = Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").unwrap();
+    board.squares[white_king] = Some(Piece::new(Color::White, PieceType::King));
+    board.squares[black_king] = Some(Piece::new(Color::Black, PieceType::King));
+    board.squares[pawn] = Some(Piece::new(Color::White, PieceType::Pawn));
+    board.turn = turn;
+    board
+}
+
+#[test]
+fn test_opposition() {
+    // Rey delante del peón en oposición: quien mueve pierde la oposición
+    assert_eq!(
+        probe("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1"),
+        Some(KpkResult::Draw)
+    );
+    assert_eq!(
+        probe("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1"),
+        Some(KpkResult::Win)
+    );
+    // Con el rey en la sexta delante del peón se gana siempre
+    assert_eq!(
+        probe("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1"),
+        Some(KpkResult::Win)
+    );
+    assert_eq!(
+        probe("4k3/8/4K3/4P3/8/8/8/8 b - - 0 1"),
+        Some(KpkResult::Win)
+    );
+    // Peón en séptima con el rey detrás: si mueve el negro es ahogado; si
+    // mueven las blancas, Rd6 Rf7 Rd7 y corona
+    assert_eq!(
+        probe("4k3/4P3/4K3/8/8/8/8/8 b - - 0 1"),
+        Some(KpkResult::Draw)
+    );
+    assert_eq!(
+        probe("4k3/4P3/4K3/8/8/8/8/8 w - - 0 1"),
+        Some(KpkResult::Win)
+    );
+    // Con el peón en sexta, quien tenga que mover en oposición decide
+    assert_eq!(
+        probe("4k3/8/4P3/4K3/8/8/8/8 w - - 0 1"),
+        Some(KpkResult::Draw)
+    );
+}
+
+#[test]
+fn test_rook_pawn_draws() {
+    // El rey débil en la esquina para al peón de torre
+    assert_eq!(probe("k7/8/K7/P7/8/8/8/8 w - - 0 1"), Some(KpkResult::Draw));
+    assert_eq!(
+        probe("k7/8/1K6/P7/8/8/8/8 w - - 0 1"),
+        Some(KpkResult::Draw)
+    );
+    assert_eq!(probe("7k/8/8/8/8/8/7P/7K w - - 0 1"), Some(KpkResult::Draw));
+    // El mismo peón gana si el rey débil no llega
+    assert_eq!(probe("8/8/8/8/8/8/k6P/7K w - - 0 1"), Some(KpkResult::Win));
+}
+
+#[test]
+fn test_rule_of_the_square() {
+    // El peón avanza dos casillas y el rey negro ya no entra en el cuadrado
+    assert_eq!(probe("8/8/8/8/8/k7/7P/K7 w - - 0 1"), Some(KpkResult::Win));
+    // Desde e4 sí lo alcanza
+    assert_eq!(
+        probe("8/8/8/8/4k3/8/7P/K7 w - - 0 1"),
+        Some(KpkResult::Draw)
+    );
+    // Si mueve el negro, puede comerse el peón indefenso
+    assert_eq!(probe("8/8/8/8/8/8/6kP/K7 b - - 0 1"), Some(KpkResult::Draw));
+}
+
+#[test]
+fn test_symmetric_positions() {
+    for fen in [
+        "8/4k3/8/4K3/4P3/8/8/8 w - - 0 1",
+        "8/4k3/8/4K3/4P3/8/8/8 b - - 0 1",
+        "k7/8/1K6/P7/8/8/8/8 w - - 0 1",
+        "8/8/8/8/8/k7/7P/K7 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(probe_kpk(&board.mirror()), probe_kpk(&board), "{}", fen);
+        assert_eq!(probe_kpk(&flipped(&board)), probe_kpk(&board), "{}", fen);
+    }
+}
+
+#[test]
+fn test_only_kpk_positions() {
+    assert_eq!(probe("4k3/8/8/8/8/8/4P3/3QK3 w - - 0 1"), None);
+    assert_eq!(probe("4k3/4p3/8/8/8/8/4P3/4K3 w - - 0 1"), None);
+    assert_eq!(probe("4k3/8/8/8/8/8/8/4K3 w - - 0 1"), None);
+}
+
+#[test]
+fn test_consistent_with_moves() {
+    // Cada posición sin coronaciones coincide con el mejor resultado de sus
+    // sucesoras, calculadas con el generador de jugadas del tablero
+    let bits = generate();
+    assert!(bits.iter().any(|word| *word != 0));
+
+    for pawn in (8..48).filter(|square| square % 8 < 4) {
+        for white_king in 0..64usize {
+            for black_king in 0..64usize {
+                if white_king == pawn
+                    || black_king == pawn
+                    || white_king == black_king
+                    || (white_king / 8).abs_diff(black_king / 8) <= 1
+                        && (white_king % 8).abs_diff(black_king % 8) <= 1
+                {
+                    continue;
+                }
+                for turn in [Color::White, Color::Black] {
+                    let board = kpk_board(turn, white_king, black_king, pawn);
+                    if board.is_king_attacked(turn.opposite()) {
+                        continue;
+                    }
+                    let results: Vec<KpkResult> = board
+                        .generate_moves()
+                        .iter()
+                        .map(|mv| {
+                            let mut next = board.clone();
+                            next.make_move(mv);
+                            probe_kpk(&next).unwrap_or(KpkResult::Draw)
+                        })
+                        .collect();
+                    let expected = match turn {
+                        Color::White if results.contains(&KpkResult::Win) => KpkResult::Win,
+                        Color::Black
+                            if !results.is_empty() && !results.contains(&KpkResult::Draw) =>
+                        {
+                            KpkResult::Win
+                        }
+                        _ => KpkResult::Draw,
+                    };
+                    assert_eq!(probe_kpk(&board), Some(expected), "{}", board.to_fen());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_evaluation_uses_bitbase() {
+    let draw = Board::from_fen("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1").unwrap();
+    assert_eq!(endgame::evaluate(&draw), Some(0));
+    let win = Board::from_fen("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1").unwrap();
+    assert!(endgame::evaluate(&win).unwrap() > KNOWN_WIN);
+    assert!(endgame::evaluate(&win.mirror()).unwrap() < -KNOWN_WIN);
+}
+
+#[test]
+fn test_search_knows_kpk() {
+    let tt = TranspositionTable::new(1);
+    // Kd6 o Kf6 mantienen el peón ganando; cualquier otra jugada, no
+    let board = Board::from_fen("4k3/8/4K3/4P3/8/8/8/8 w - - 0 1").unwrap();
+    let result = search(&board, 4, &tt);
+    assert!(result.score > KNOWN_WIN);
+    let best = result.best_move.expect("Sin jugada");
+    let mut next = board.clone();
+    next.make_move(&best);
+    assert_eq!(probe_kpk(&next), Some(KpkResult::Win), "{}", best);
+
+    // En tablas la búsqueda lo sabe aunque tenga un peón de más
+    let board = Board::from_fen("k7/8/1K6/P7/8/8/8/8 w - - 0 1").unwrap();
+    assert_eq!(search(&board, 6, &tt).score, 0);
+}
@@ -0,0 +1,78 @@
+use crate::{
+    mate::{MateSolver, find_mate},
+    types::Board,
+};
+
+fn mate_line(fen: &str, max_moves: u32) -> Option<Vec<String>> {
+    let board = Board::from_fen(fen).unwrap();
+    find_mate(&board, max_moves).map(|line| line.iter().map(|mv| mv.to_string()).collect())
+}
+
+#[test]
+fn test_mate_in_one() {
+    let fen = "r1bqk1nr/pppp1ppp/2n5/2b1p3/2B1P3/5Q2/PPPP1PPP/RNB1K1NR w KQkq - 0 1";
+    assert_eq!(mate_line(fen, 1), Some(vec!["f3f7".to_string()]));
+}
+
+#[test]
+fn test_mate_in_two_sacrifice() {
+    // 1. Qe8+! Rxe8 2. Rxe8#: la única defensa es capturar la dama
+    let fen = "r5k1/5ppp/8/4Q3/8/8/8/4R1K1 w - - 0 1";
+    assert_eq!(
+        mate_line(fen, 3),
+        Some(vec!["e5e8".into(), "a8e8".into(), "e1e8".into()])
+    );
+}
+
+#[test]
+fn test_finds_shortest_mate() {
+    // Mate de la coz: 1. Qb3+ Kh8 2. Nf7+ Kg8 3. Nh6+ Kh8 4. Qg8+ Rxg8 5. Nf7#
+    let fen = "5rk1/6pp/8/6N1/8/8/6PP/3Q2K1 w - - 0 1";
+    assert_eq!(mate_line(fen, 4), None);
+
+    let line = mate_line(fen, 5).expect("Hay mate en 5");
+    assert_eq!(line.len(), 9);
+    assert_eq!(line[0], "d1b3");
+    assert_eq!(line[8], "h6f7");
+}
+
+#[test]
+fn test_no_mate() {
+    let board = Board::initial_position();
+    assert!(find_mate(&board, 2).is_none());
+}
+
+#[test]
+fn test_stalemate_is_not_mate() {
+    // Con la búsqueda completa, Qg6 ahogaría; el mate es Qg7
+    let board = Board::from_fen("7k/8/5K2/8/8/8/8/6Q1 w - - 0 1").unwrap();
+    let mut solver = MateSolver::new();
+    solver.checks_only = false;
+    let line = solver.solve(&board, 1).expect("Hay mate en 1");
+    assert_eq!(line[0].to_string(), "g1g7");
+}
+
+#[test]
+fn test_quiet_moves_need_full_width() {
+    // 1. Kg6 (tranquila) y mate con la torre: la búsqueda solo de jaques no lo ve
+    let board = Board::from_fen("7k/8/5K2/8/8/8/8/R7 w - - 0 1").unwrap();
+    let mut solver = MateSolver::new();
+    assert!(solver.solve(&board, 2).is_none());
+
+    solver.checks_only = false;
+    let line = solver.solve(&board, 2).expect("Hay mate en 2");
+    assert_eq!(line.len(), 3);
+}
+
+#[test]
+fn test_stop_interrupts_solver() {
+    // Sin mate posible la búsqueda completa a 6 jugadas tarda mucho: con
+    // "stop" ya mandado se corta en cuanto mira el indicador
+    let board = Board::initial_position();
+    let mut solver = MateSolver::new();
+    solver.checks_only = false;
+    solver.shared.stop();
+    assert!(solver.solve(&board, 6).is_none());
+    assert!(solver.stopped);
+    assert!(solver.nodes <= 1024);
+}
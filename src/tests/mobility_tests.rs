@@ -0,0 +1,104 @@
+use crate::{
+    mobility::{
+        MOBILITY_BASE, is_outpost, is_rook_on_seventh, mobility, piece_activity, same_color_pawns,
+    },
+    params::{BISHOP_PAIR, MOBILITY},
+    types::{Board, Color, PieceType},
+};
+
+fn from_fen(fen: &str) -> Board {
+    Board::from_fen(fen).expect("FEN inválido")
+}
+
+#[test]
+fn test_mobility_excludes_own_pieces_and_pawn_attacks() {
+    // Caballo en e4: sin f2 (pieza propia) ni c5 (la controla d6)
+    let board = from_fen("4k3/8/3p4/8/4N3/8/5P2/4K3 w - - 0 1");
+    assert_eq!(mobility(&board, 28), 6);
+
+    let board = from_fen("4k3/8/8/8/4N3/8/8/4K3 w - - 0 1");
+    assert_eq!(mobility(&board, 28), 8);
+}
+
+#[test]
+fn test_rook_files() {
+    let open = piece_activity(&from_fen("4k3/8/8/8/8/8/8/3RK3 w - - 0 1"));
+    let semi_open = piece_activity(&from_fen("4k3/3p4/8/8/8/8/8/3RK3 w - - 0 1"));
+    let closed = piece_activity(&from_fen("4k3/3p4/8/8/8/8/3P4/3RK3 w - - 0 1"));
+    assert!(open.mg > semi_open.mg);
+    assert!(semi_open.mg > closed.mg);
+}
+
+#[test]
+fn test_rook_on_seventh() {
+    // Encierra al rey en la octava
+    assert!(is_rook_on_seventh(
+        &from_fen("7k/R7/8/8/8/8/8/4K3 w - - 0 1"),
+        48,
+        Color::White
+    ));
+    // Ni rey en la octava ni peones en la séptima
+    assert!(!is_rook_on_seventh(
+        &from_fen("8/R7/7k/8/8/8/8/4K3 w - - 0 1"),
+        48,
+        Color::White
+    ));
+    // Ataca peones de la séptima
+    assert!(is_rook_on_seventh(
+        &from_fen("8/R5p1/7k/8/8/8/8/4K3 w - - 0 1"),
+        48,
+        Color::White
+    ));
+    // Torre negra en la segunda
+    assert!(is_rook_on_seventh(
+        &from_fen("4k3/8/8/8/8/8/r7/7K b - - 0 1"),
+        8,
+        Color::Black
+    ));
+}
+
+#[test]
+fn test_knight_outpost() {
+    // Caballo en d5 defendido por e4; ningún peón negro puede echarlo
+    let outpost = from_fen("4k3/p6p/8/3N4/4P3/8/8/4K3 w - - 0 1");
+    assert!(is_outpost(&outpost, 35, Color::White));
+
+    // Con un peón en c7 las negras pueden jugar c6
+    let chased = from_fen("4k3/p1p4p/8/3N4/4P3/8/8/4K3 w - - 0 1");
+    assert!(!is_outpost(&chased, 35, Color::White));
+
+    // Sin defensa de peón no es puesto avanzado
+    let undefended = from_fen("4k3/p6p/8/3N4/8/8/8/4K3 w - - 0 1");
+    assert!(!is_outpost(&undefended, 35, Color::White));
+}
+
+#[test]
+fn test_bishop_pair() {
+    let pair = from_fen("6k1/8/8/8/3BB3/8/8/1K6 w - - 0 1");
+    let single = from_fen("6k1/8/8/8/3B4/8/8/1K6 w - - 0 1");
+
+    let bishop = PieceType::Bishop as usize;
+    let e4_bishop = MOBILITY[bishop] * (mobility(&pair, 28) as i32 - MOBILITY_BASE[bishop]);
+    assert_eq!(
+        piece_activity(&pair) - piece_activity(&single),
+        e4_bishop + BISHOP_PAIR
+    );
+}
+
+#[test]
+fn test_bad_bishop() {
+    // Alfil de casillas oscuras en c1: d4 es oscura, e4 clara
+    let board = from_fen("4k3/8/8/8/3PP3/8/8/2B1K3 w - - 0 1");
+    assert_eq!(same_color_pawns(&board, 2, Color::White), 1);
+
+    // c3, d4 y e3 son oscuras: el alfil queda encerrado por sus peones
+    let bad = from_fen("4k3/8/8/8/3P4/2P1P3/8/2B1K3 w - - 0 1");
+    assert_eq!(same_color_pawns(&bad, 2, Color::White), 3);
+    assert!(piece_activity(&bad).mg < piece_activity(&board).mg);
+}
+
+#[test]
+fn test_piece_activity_symmetry() {
+    let board = from_fen("r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2NB1N2/PP3PPP/R2Q1RK1 w - - 0 1");
+    assert_eq!(piece_activity(&board), -piece_activity(&board.mirror()));
+}
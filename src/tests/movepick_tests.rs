@@ -0,0 +1,91 @@
+use crate::{
+    movepick::{MoveOrdering, MovePicker},
+    types::{Board, Move},
+};
+
+fn parse(board: &Board, mv: &str) -> Move {
+    board.parse_move(mv).expect("Movimiento ilegal")
+}
+
+#[test]
+fn test_tt_move_then_captures_first() {
+    // Las blancas pueden capturar la dama (d5) con el peón e4 o con el caballo c3
+    let board = Board::from_fen("4k3/8/8/3q4/4P3/2N5/8/4K3 w - - 0 1").unwrap();
+    let ordering = MoveOrdering::new();
+    let tt_move = parse(&board, "e1f2");
+
+    let moves: Vec<Move> = MovePicker::new(
+        &board,
+        board.generate_moves(),
+        Some(tt_move),
+        &ordering,
+        0,
+        None,
+    )
+    .collect();
+
+    assert_eq!(moves[0], tt_move, "La jugada de la TT va primero");
+    assert_eq!(
+        moves[1],
+        parse(&board, "e4d5"),
+        "PxD antes que CxD (MVV-LVA)"
+    );
+    assert_eq!(moves[2], parse(&board, "c3d5"));
+}
+
+#[test]
+fn test_killers_and_history() {
+    let board = Board::initial_position();
+    let mut ordering = MoveOrdering::new();
+    let killer = parse(&board, "g1f3");
+    let history_move = parse(&board, "b1c3");
+    let tried = parse(&board, "a2a3");
+
+    // b1c3 corta a mucha profundidad en otro ply: solo gana historia
+    ordering.update_quiet_cutoff(&board, history_move, &[tried], 8, 5, None);
+    // g1f3 corta en ply 0: pasa a ser killer
+    ordering.update_quiet_cutoff(&board, killer, &[], 1, 0, None);
+
+    let moves: Vec<Move> =
+        MovePicker::new(&board, board.generate_moves(), None, &ordering, 0, None).collect();
+    assert_eq!(moves[0], killer);
+    assert_eq!(moves[1], history_move);
+    assert_eq!(
+        moves.last(),
+        Some(&tried),
+        "La jugada que no cortó se penaliza"
+    );
+}
+
+#[test]
+fn test_history_gravity_is_bounded() {
+    let board = Board::initial_position();
+    let mut ordering = MoveOrdering::new();
+    let mv = parse(&board, "e2e4");
+
+    for _ in 0..1000 {
+        ordering.update_quiet_cutoff(&board, mv, &[], 30, 0, None);
+    }
+    let value = ordering.history(board.turn, &mv);
+    assert!(
+        value > 0 && value <= 16384,
+        "Historia fuera de rango: {}",
+        value
+    );
+}
+
+#[test]
+fn test_countermove() {
+    let mut board = Board::initial_position();
+    let e4 = parse(&board, "e2e4");
+    board.make_move(&e4);
+    let reply = parse(&board, "c7c5");
+
+    let mut ordering = MoveOrdering::new();
+    ordering.update_quiet_cutoff(&board, reply, &[], 1, 3, Some(e4));
+
+    assert_eq!(ordering.countermove(&board, Some(e4)), Some(reply));
+    let moves: Vec<Move> =
+        MovePicker::new(&board, board.generate_moves(), None, &ordering, 1, Some(e4)).collect();
+    assert_eq!(moves[0], reply);
+}
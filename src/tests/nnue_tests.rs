@@ -0,0 +1,198 @@
+use crate::{
+    nnue::{Accumulator, AccumulatorStack, Network, simd},
+    search::{SearchConfig, SearchThread},
+    skill::Rng,
+    tt::TranspositionTable,
+    types::Board,
+};
+use std::{env, fs, sync::Arc};
+
+// Red pequeña con pesos aleatorios: Network::random(16, NET_SEED).to_bytes()
+const RANDOM_NET: &[u8] = include_bytes!("data/random_16.nnue");
+const NET_SEED: u64 = 42;
+
+// Posiciones con enroques, capturas al paso y coronaciones a mano
+const FENS: [&str; 4] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "rnbqkbnr/ppp1p1pp/8/3pPp2/8/8/PPPP1PPP/RNBQKBNR w KQkq f6 0 3",
+    "n1n5/PPPk4/8/8/8/8/4Kppp/5N1N b - - 0 1",
+];
+
+fn random_net() -> Network {
+    Network::from_bytes(RANDOM_NET).expect("La red de prueba no carga")
+}
+
+#[test]
+fn test_checked_in_net_loads() {
+    let net = random_net();
+    assert_eq!(net.hidden, 16);
+    assert_eq!(net, Network::random(16, NET_SEED));
+    assert_eq!(net.to_bytes(), RANDOM_NET);
+}
+
+#[test]
+fn test_load_from_file() {
+    let path = env::temp_dir().join(format!("chess-bot-gm-{}.nnue", std::process::id()));
+    fs::write(&path, RANDOM_NET).unwrap();
+    let loaded = Network::load(path.to_str().unwrap());
+    fs::remove_file(&path).unwrap();
+    assert_eq!(loaded, Ok(random_net()));
+
+    assert!(Network::load("no/existe.nnue").is_err());
+}
+
+#[test]
+fn test_rejects_invalid_files() {
+    assert!(Network::from_bytes(b"").is_err());
+    assert!(Network::from_bytes(b"XXXX\x01\0\0\0\x10\0\0\0").is_err());
+    // Truncada
+    assert!(Network::from_bytes(&RANDOM_NET[..RANDOM_NET.len() - 2]).is_err());
+    // Versión desconocida
+    let mut bytes = RANDOM_NET.to_vec();
+    bytes[4] = 9;
+    assert!(Network::from_bytes(&bytes).is_err());
+    // Sin neuronas ocultas
+    let mut bytes = RANDOM_NET.to_vec();
+    bytes[8] = 0;
+    assert!(Network::from_bytes(&bytes).is_err());
+}
+
+#[test]
+fn test_incremental_matches_refresh() {
+    // 40 neuronas: la parte vectorial y el resto escalar
+    for net in [random_net(), Network::random(40, 7)] {
+        let mut rng = Rng::new(1);
+        for fen in FENS {
+            let mut board = Board::from_fen(fen).unwrap();
+            let mut stack = AccumulatorStack::new(net.hidden, 64);
+            stack.refresh(&net, &board);
+
+            for ply in 0..60 {
+                let moves = board.generate_moves();
+                if moves.is_empty() {
+                    break;
+                }
+                let mv = moves[rng.below(moves.len() as u64) as usize];
+                let mut next = board.clone();
+                next.make_move(&mv);
+                stack.push(ply, &net, &board, &mv);
+
+                let mut fresh = Accumulator::new(net.hidden);
+                fresh.refresh(&net, &next);
+                assert_eq!(stack.get(ply + 1), &fresh, "{} tras {}", fen, mv);
+                assert_eq!(
+                    net.output(stack.get(ply + 1), next.turn),
+                    net.evaluate(&next)
+                );
+                board = next;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_update_covers_every_move() {
+    // Todas las jugadas de cada posición y de su espejo: así entran los
+    // enroques, las capturas al paso y las coronaciones de los dos bandos
+    let net = random_net();
+    for fen in FENS {
+        let board = Board::from_fen(fen).unwrap();
+        for board in [board.mirror(), board] {
+            let mut stack = AccumulatorStack::new(net.hidden, 2);
+            stack.refresh(&net, &board);
+            for mv in board.generate_moves() {
+                let mut next = board.clone();
+                next.make_move(&mv);
+                stack.push(0, &net, &board, &mv);
+
+                let mut fresh = Accumulator::new(net.hidden);
+                fresh.refresh(&net, &next);
+                assert_eq!(stack.get(1), &fresh, "{} tras {}", board.to_fen(), mv);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_null_move_keeps_accumulator() {
+    let net = random_net();
+    let mut board = Board::from_fen(FENS[1]).unwrap();
+    let mut stack = AccumulatorStack::new(net.hidden, 4);
+    stack.refresh(&net, &board);
+    stack.push_null(0);
+    board.make_null_move();
+    assert_eq!(stack.get(1), stack.get(0));
+    // Mismas piezas, pero ahora mueve el otro bando
+    assert_eq!(net.output(stack.get(1), board.turn), net.evaluate(&board));
+}
+
+#[test]
+fn test_simd_matches_scalar() {
+    let mut rng = Rng::new(5);
+    let mut values =
+        |count: usize| -> Vec<i16> { (0..count).map(|_| rng.below(1001) as i16 - 500).collect() };
+    for len in [16, 37, 64, 5] {
+        let weights = values(len);
+        let start = values(len);
+
+        let (mut fast, mut slow) = (start.clone(), start.clone());
+        simd::add(&mut fast, &weights);
+        simd::scalar::add(&mut slow, &weights);
+        assert_eq!(fast, slow);
+
+        simd::sub(&mut fast, &weights);
+        simd::scalar::sub(&mut slow, &weights);
+        assert_eq!(fast, start);
+        assert_eq!(slow, start);
+
+        assert_eq!(
+            simd::crelu_dot(&start, &weights),
+            simd::scalar::crelu_dot(&start, &weights)
+        );
+    }
+}
+
+#[test]
+fn test_perspective_symmetry() {
+    // Cada bando se ve a sí mismo igual: voltear el tablero y los colores no
+    // cambia la evaluación del bando que mueve
+    let net = random_net();
+    for fen in FENS {
+        let board = Board::from_fen(fen).unwrap();
+        assert_eq!(
+            net.evaluate(&board),
+            net.evaluate(&board.mirror()),
+            "{}",
+            fen
+        );
+    }
+}
+
+#[test]
+fn test_search_with_nnue() {
+    // La red aleatoria no sabe de material: en posiciones con muchas capturas
+    // la búsqueda de quietud no encuentra cortes y se eterniza
+    let config = SearchConfig {
+        nnue: Some(Arc::new(random_net())),
+        ..SearchConfig::default()
+    };
+    let search = |board: &Board, depth: u32| {
+        let tt = TranspositionTable::new(1);
+        let mut thread = SearchThread::new(&tt);
+        thread.config = config.clone();
+        thread.search(board, depth)
+    };
+
+    let board = Board::initial_position();
+    let best = search(&board, 4).best_move.expect("Sin jugada");
+    assert!(board.generate_moves().contains(&best));
+
+    // El mate se encuentra igual: no depende de la evaluación
+    let board = Board::from_fen("6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1").unwrap();
+    let result = search(&board, 3);
+    assert_eq!(
+        result.best_move.map(|mv| mv.to_string()),
+        Some("e1e8".into())
+    );
+}
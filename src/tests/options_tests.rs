@@ -0,0 +1,102 @@
+use crate::options::*;
+
+#[test]
+fn test_default_values() {
+    let options = EngineOptions::new();
+    assert_eq!(options.spin(HASH), 16);
+    assert_eq!(options.spin(THREADS), 1);
+    assert_eq!(options.spin(MULTI_PV), 1);
+    assert!(!options.check(PONDER));
+    assert!(!options.check(UCI_LIMIT_STRENGTH));
+    assert!(!options.check(USE_NNUE));
+    assert_eq!(options.text(EVAL_FILE), "");
+}
+
+#[test]
+fn test_uci_option_lines() {
+    let options = EngineOptions::new();
+    let lines: Vec<String> = options.iter().map(|o| o.to_string()).collect();
+
+    assert!(lines.contains(&"option name Hash type spin default 16 min 1 max 4096".to_string()));
+    assert!(lines.contains(&"option name Ponder type check default false".to_string()));
+    assert!(lines.contains(&"option name Clear Hash type button".to_string()));
+    assert!(lines.contains(&"option name EvalFile type string default <empty>".to_string()));
+}
+
+#[test]
+fn test_parse_setoption_multi_word_name() {
+    let parts: Vec<&str> = "name Move Overhead value 100".split_whitespace().collect();
+    let (name, value) = parse_setoption(&parts).unwrap();
+    assert_eq!(name, "Move Overhead");
+    assert_eq!(value.as_deref(), Some("100"));
+
+    let parts: Vec<&str> = "name Clear Hash".split_whitespace().collect();
+    let (name, value) = parse_setoption(&parts).unwrap();
+    assert_eq!(name, "Clear Hash");
+    assert_eq!(value, None);
+
+    assert!(parse_setoption(&["value", "3"]).is_err());
+}
+
+#[test]
+fn test_set_spin_validates_range() {
+    let mut options = EngineOptions::new();
+
+    assert_eq!(options.set("move overhead", Some("250")), Ok(MOVE_OVERHEAD));
+    assert_eq!(options.spin(MOVE_OVERHEAD), 250);
+
+    assert!(options.set(HASH, Some("0")).is_err());
+    assert!(options.set(HASH, Some("abc")).is_err());
+    assert!(options.set(HASH, None).is_err());
+    // Un valor rechazado no cambia el anterior
+    assert_eq!(options.spin(HASH), 16);
+}
+
+#[test]
+fn test_set_check_and_button() {
+    let mut options = EngineOptions::new();
+
+    assert!(options.set(PONDER, Some("true")).is_ok());
+    assert!(options.check(PONDER));
+    assert!(options.set(PONDER, Some("yes")).is_err());
+
+    assert_eq!(options.set("clear hash", None), Ok(CLEAR_HASH));
+}
+
+#[test]
+fn test_unknown_option_is_reported() {
+    let mut options = EngineOptions::new();
+    let result = options.set("Contempt", Some("20"));
+    assert!(result.unwrap_err().contains("Contempt"));
+}
+
+#[test]
+fn test_combo_and_string_options() {
+    let combo = EngineOption::new(
+        "Style",
+        OptionKind::Combo {
+            default: "Normal",
+            vars: &["Solid", "Normal", "Risky"],
+        },
+    );
+    assert_eq!(
+        combo.to_string(),
+        "option name Style type combo default Normal var Solid var Normal var Risky"
+    );
+
+    let string = EngineOption::new("EvalFile", OptionKind::String { default: "" });
+    assert_eq!(
+        string.to_string(),
+        "option name EvalFile type string default <empty>"
+    );
+}
+
+#[test]
+fn test_skill_options() {
+    let mut options = EngineOptions::new();
+    assert_eq!(options.spin(SKILL_LEVEL), 20);
+    assert_eq!(options.spin(SKILL_SEED), 0);
+    assert!(options.set("skill level", Some("21")).is_err());
+    assert_eq!(options.set("Skill Level", Some("3")), Ok(SKILL_LEVEL));
+    assert_eq!(options.spin(SKILL_LEVEL), 3);
+}
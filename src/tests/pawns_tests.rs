@@ -0,0 +1,152 @@
+use crate::{
+    pawns::{PawnStructure, PawnTable, analyze, passed_pawns},
+    types::{Board, Color},
+};
+
+fn structure(fen: &str) -> PawnStructure {
+    PawnStructure::new(&Board::from_fen(fen).expect("FEN inválido"))
+}
+
+// Parte de final de los peones pasados de la posición
+fn passed_eg(fen: &str) -> i32 {
+    let board = Board::from_fen(fen).expect("FEN inválido");
+    passed_pawns(&board, &analyze(&board)).eg
+}
+
+#[test]
+fn test_doubled_and_isolated() {
+    // Peones blancos doblados y aislados en c2 y c3
+    let pawns = structure("4k3/8/8/8/8/2P5/2P5/4K3 w - - 0 1");
+    assert!(pawns.is_doubled(10, Color::White));
+    assert!(pawns.is_isolated(18, Color::White));
+
+    let pawns = structure("4k3/8/8/8/8/8/2PP4/4K3 w - - 0 1");
+    assert!(!pawns.is_doubled(10, Color::White));
+    assert!(!pawns.is_isolated(10, Color::White));
+}
+
+#[test]
+fn test_passed_pawns() {
+    // a2 es pasado; e2 y d5 se frenan mutuamente
+    let board = Board::from_fen("4k3/8/8/3p4/8/8/P3P3/4K3 w - - 0 1").unwrap();
+    let pawns = PawnStructure::new(&board);
+    assert!(pawns.is_passed(8, Color::White));
+    assert!(!pawns.is_passed(12, Color::White));
+    assert!(!pawns.is_passed(35, Color::Black));
+    assert_eq!(analyze(&board).passed, [1 << 8, 0]);
+
+    // Los peones de la misma columna tampoco dejan pasar
+    let pawns = structure("4k3/7p/8/8/8/8/7P/4K3 w - - 0 1");
+    assert!(!pawns.is_passed(15, Color::White));
+    assert!(!pawns.is_passed(55, Color::Black));
+}
+
+#[test]
+fn test_backward_pawn() {
+    // d3 no tiene apoyo (e4 está delante) y c5 controla d4
+    let pawns = structure("4k3/8/8/2p5/4P3/3P4/8/4K3 w - - 0 1");
+    assert!(pawns.is_backward(19, Color::White));
+    assert!(!pawns.is_backward(28, Color::White));
+
+    // Sin el peón de c5 puede avanzar
+    let pawns = structure("4k3/8/8/8/4P3/3P4/8/4K3 w - - 0 1");
+    assert!(!pawns.is_backward(19, Color::White));
+}
+
+#[test]
+fn test_connected_pawns() {
+    // Falange d4-e4
+    let pawns = structure("4k3/8/8/8/3PP3/8/8/4K3 w - - 0 1");
+    assert!(pawns.is_connected(27, Color::White));
+    assert!(pawns.is_connected(28, Color::White));
+
+    // e3 defiende a d4, pero nadie defiende a e3
+    let pawns = structure("4k3/8/8/8/3P4/4P3/8/4K3 w - - 0 1");
+    assert!(pawns.is_connected(27, Color::White));
+    assert!(!pawns.is_connected(20, Color::White));
+
+    // Lo mismo para las negras: d5 defendido por e6
+    let pawns = structure("4k3/8/4p3/3p4/8/8/8/4K3 w - - 0 1");
+    assert!(pawns.is_connected(35, Color::Black));
+    assert!(!pawns.is_connected(44, Color::Black));
+}
+
+#[test]
+fn test_pawn_islands() {
+    let pawns = structure("4k3/pppppppp/8/8/8/8/PP1P1PP1/4K3 w - - 0 1");
+    assert_eq!(pawns.islands(Color::White), 3);
+    assert_eq!(pawns.islands(Color::Black), 1);
+}
+
+#[test]
+fn test_weak_structure_scores_lower() {
+    // Mismos peones, sanos o doblados y aislados
+    let healthy = analyze(&Board::from_fen("4k3/8/8/8/8/8/2PPP3/4K3 w - - 0 1").unwrap());
+    let weak = analyze(&Board::from_fen("4k3/8/8/8/8/2P5/2P1P3/4K3 w - - 0 1").unwrap());
+    assert!(healthy.score.mg > weak.score.mg);
+    assert!(healthy.score.eg > weak.score.eg);
+}
+
+#[test]
+fn test_passed_pawn_scales_with_rank() {
+    let e4 = passed_eg("7k/8/8/8/4P3/8/8/K7 w - - 0 1");
+    let e5 = passed_eg("7k/8/8/4P3/8/8/8/K7 w - - 0 1");
+    let e6 = passed_eg("7k/8/4P3/8/8/8/8/K7 w - - 0 1");
+    assert!(e4 > 0);
+    assert!(e5 > e4);
+    assert!(e6 > e5);
+}
+
+#[test]
+fn test_blocked_passed_pawn_scores_lower() {
+    let free = passed_eg("7k/8/4P3/8/8/8/8/K7 w - - 0 1");
+    let blocked = passed_eg("7k/4n3/4P3/8/8/8/8/K7 w - - 0 1");
+    assert!(blocked < free);
+}
+
+#[test]
+fn test_passed_pawn_king_distance() {
+    // Con el rey rival delante del peón vale menos que con el rey lejos
+    let king_far = passed_eg("k7/8/8/3P4/8/8/8/4K3 w - - 0 1");
+    let king_near = passed_eg("8/8/3k4/3P4/8/8/8/4K3 w - - 0 1");
+    assert!(king_far > king_near);
+
+    // Y vale más con el rey propio acompañándolo
+    let own_king_near = passed_eg("k7/8/8/3PK3/8/8/8/8 w - - 0 1");
+    assert!(own_king_near > king_far);
+}
+
+#[test]
+fn test_pawn_structure_symmetry() {
+    let board = Board::from_fen("4k3/pp3p1p/2p1p3/3p2p1/3P4/2P1P1P1/PP3P1P/4K3 w - - 0 1").unwrap();
+    let mirrored = board.mirror();
+    assert_eq!(analyze(&board).score, -analyze(&mirrored).score);
+    assert_eq!(
+        passed_pawns(&board, &analyze(&board)),
+        -passed_pawns(&mirrored, &analyze(&mirrored))
+    );
+}
+
+#[test]
+fn test_pawn_table_caches_structure() {
+    let mut table = PawnTable::new();
+    let board = Board::initial_position();
+    let entry = table.probe(&board);
+    assert_eq!(table.hits, 0);
+    assert_eq!(entry.score, analyze(&board).score);
+
+    // Mover una pieza no cambia la estructura: acierto en la tabla
+    let mut after_knight = board.clone();
+    after_knight.make_move(&after_knight.parse_move("g1f3").unwrap());
+    let cached = table.probe(&after_knight);
+    assert_eq!(table.hits, 1);
+    assert_eq!(cached.key, entry.key);
+
+    // Mover un peón sí
+    let mut after_pawn = board.clone();
+    after_pawn.make_move(&after_pawn.parse_move("e2e4").unwrap());
+    let fresh = table.probe(&after_pawn);
+    assert_eq!(table.hits, 1);
+    assert_eq!(fresh.key, after_pawn.pawn_hash);
+    assert_eq!(fresh.score, analyze(&after_pawn).score);
+}
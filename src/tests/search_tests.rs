@@ -1,5 +1,10 @@
 use crate::{
-    search::search_best_move,
+    search::{
+        SearchConfig, SearchThread, analyze_root_moves, format_score, search, search_best_move,
+        search_multi_pv,
+    },
+    timeman::SearchLimits,
+    tt::TranspositionTable,
     types::{Board, Square},
 };
 
@@ -29,29 +34,34 @@ fn test_mate_in_one() {
 #[test]
 fn test_dont_eat_protected_piece() {
     // Situación: Dama Blanca en d1. Peón Negro en d4. Torre Negra en d8 defendiendo al peón.
-    // Depth 1: "Como el peón (+100 puntos)". ERROR -> Luego pierdo la Dama (-900).
-    // Depth 3: "Si como el peón, me comen la dama. Mejor hago otra cosa".
+    // Sin quietud, a depth 1 el bot "comía el peón (+100 puntos)" y luego perdía la Dama (-900).
+    // Con la búsqueda de quietud ya ve la recaptura incluso a depth 1.
 
     let fen = "3r4/8/8/8/3p4/8/8/3Q4 w - - 0 1";
 
-    // --- PRUEBA CON DEPTH 1 ---
-    // Con depth 1, solo ve la captura inmediata.
-    let (from_d1, to_d1) = get_best_move_coords(fen, 1);
-    // d1=3, d4=27
-    assert_eq!(from_d1, 3);
-    assert_eq!(
-        to_d1, 27,
-        "A profundidad 1, el bot debería ser codicioso y comer el peón suicida"
-    );
+    for depth in 1..=3 {
+        let (_from, to) = get_best_move_coords(fen, depth);
+
+        // No debe mover a d4 (27). Cualquier otro sitio es mejor.
+        assert_ne!(
+            to, 27,
+            "A profundidad {}, el bot NO debería comer el peón protegido",
+            depth
+        );
+    }
+}
 
-    // --- PRUEBA CON DEPTH 3 ---
-    // Con depth 3, ve la respuesta del oponente.
-    let (from_d3, to_d3) = get_best_move_coords(fen, 3);
+#[test]
+fn test_quiescence_sees_exchange_at_leaf() {
+    // Caballo blanco en e5 y peón negro en d7 defendido por la dama y el rey.
+    // Sin quietud, a depth 1 Nxd7 parece ganar un peón; en realidad cuelga el caballo.
+    let fen = "3qk3/3p4/5p2/4N3/3P4/8/8/4K3 w - - 0 1";
+    let (from, to) = get_best_move_coords(fen, 1);
 
-    // No debe mover a d4 (27). Cualquier otro sitio es mejor.
-    assert_ne!(
-        to_d3, 27,
-        "A profundidad 3, el bot NO debería comer el peón protegido"
+    // e5=36, d7=51
+    assert!(
+        !(from == 36 && to == 51),
+        "Nxd7 pierde el caballo a cambio de un peón"
     );
 }
 
@@ -88,3 +98,335 @@ fn test_defend_mate() {
         "Debe encontrar el único movimiento legal para salir de jaque"
     );
 }
+
+const BENCH_FENS: [&str; 3] = [
+    "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+    "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+    "8/2k5/3p4/p2P1p2/P2P1P2/8/3K4/8 w - - 0 1",
+];
+
+fn count_nodes(fen: &str, depth: u32, config: SearchConfig) -> u64 {
+    let board = Board::from_fen(fen).expect("FEN inválido");
+    let tt = TranspositionTable::new(1);
+    let mut thread = SearchThread::new(&tt);
+    thread.config = config;
+    thread.search(&board, depth).nodes
+}
+
+fn total_nodes(fens: &[&str], depth: u32, config: SearchConfig) -> u64 {
+    fens.iter()
+        .map(|fen| count_nodes(fen, depth, config.clone()))
+        .sum()
+}
+
+#[test]
+fn test_move_ordering_reduces_nodes() {
+    // Benchmark de nodos: la misma búsqueda con y sin ordenación de jugadas
+    let ordered = total_nodes(&BENCH_FENS[..2], 3, SearchConfig::default());
+    let unordered = total_nodes(
+        &BENCH_FENS[..2],
+        3,
+        SearchConfig {
+            move_ordering: false,
+            ..SearchConfig::default()
+        },
+    );
+
+    println!(
+        "Ordenado: {} nodos, sin ordenar: {} nodos",
+        ordered, unordered
+    );
+    assert!(
+        ordered * 2 < unordered,
+        "La ordenación debería al menos reducir los nodos a la mitad"
+    );
+}
+
+#[test]
+fn test_pvs_and_aspiration_reduce_nodes() {
+    // Benchmark de nodos a la misma profundidad con ventana completa y con PVS + aspiración
+    let full_window = SearchConfig {
+        pvs: false,
+        aspiration_windows: false,
+        ..SearchConfig::default()
+    };
+    let pvs_only = SearchConfig {
+        aspiration_windows: false,
+        ..SearchConfig::default()
+    };
+
+    let full = total_nodes(&BENCH_FENS, 5, full_window);
+    let pvs = total_nodes(&BENCH_FENS, 5, pvs_only);
+    let both = total_nodes(&BENCH_FENS, 5, SearchConfig::default());
+
+    println!(
+        "Ventana completa: {} nodos, PVS: {} nodos, PVS + aspiración: {} nodos",
+        full, pvs, both
+    );
+    assert!(pvs < full, "PVS debería visitar menos nodos");
+    assert!(both < full, "PVS + aspiración debería visitar menos nodos");
+}
+
+#[test]
+fn test_search_returns_pv() {
+    let board = Board::from_fen("r5k1/5ppp/8/4Q3/8/8/8/4R1K1 w - - 0 1").unwrap();
+    let tt = TranspositionTable::new(1);
+    let result = SearchThread::new(&tt).search(&board, 3);
+
+    // 1. Qe8+ Rxe8 2. Rxe8#
+    let pv: Vec<String> = result.pv.iter().map(|mv| mv.to_string()).collect();
+    assert_eq!(pv, ["e5e8", "a8e8", "e1e8"]);
+    assert_eq!(result.best_move, result.pv.first().copied());
+    assert_eq!(format_score(result.score), "mate 2");
+}
+
+#[test]
+fn test_selective_search_reduces_nodes() {
+    let plain = SearchConfig {
+        null_move: false,
+        lmr: false,
+        reverse_futility: false,
+        futility: false,
+        late_move_pruning: false,
+        ..SearchConfig::default()
+    };
+
+    let full = total_nodes(&BENCH_FENS, 5, plain);
+    let selective = total_nodes(&BENCH_FENS, 5, SearchConfig::default());
+
+    println!("Sin podas: {} nodos, con podas: {} nodos", full, selective);
+    assert!(
+        selective * 2 < full,
+        "Las podas deberían ahorrar más de la mitad"
+    );
+}
+
+#[test]
+fn test_each_pruning_toggle_keeps_tactics() {
+    // Con cada técnica activada por separado se siguen viendo los mates y las capturas
+    let plain = SearchConfig {
+        null_move: false,
+        lmr: false,
+        reverse_futility: false,
+        futility: false,
+        late_move_pruning: false,
+        ..SearchConfig::default()
+    };
+    let configs = [
+        SearchConfig {
+            null_move: true,
+            ..plain.clone()
+        },
+        SearchConfig {
+            lmr: true,
+            ..plain.clone()
+        },
+        SearchConfig {
+            reverse_futility: true,
+            ..plain.clone()
+        },
+        SearchConfig {
+            futility: true,
+            ..plain.clone()
+        },
+        SearchConfig {
+            late_move_pruning: true,
+            ..plain.clone()
+        },
+    ];
+
+    for config in configs {
+        let board = Board::from_fen("r5k1/5ppp/8/4Q3/8/8/8/4R1K1 w - - 0 1").unwrap();
+        let tt = TranspositionTable::new(1);
+        let mut thread = SearchThread::new(&tt);
+        thread.config = config.clone();
+        let result = thread.search(&board, 4);
+        assert_eq!(
+            result.best_move.map(|mv| mv.to_string()),
+            Some("e5e8".to_string()),
+            "Configuración {:?}",
+            config
+        );
+    }
+}
+
+#[test]
+fn test_null_move_keeps_pawn_endgame_zugzwang() {
+    // Solo reyes y peones: aquí el movimiento nulo no se intenta, porque pasar
+    // el turno es justo lo que no se puede hacer en un zugzwang. Tras Rf5 el
+    // negro solo puede mover el peón y lo pierde; si se le dejara pasar, la
+    // búsqueda vería tablas y preferiría g6 (+0,69 a profundidad 6)
+    let board = Board::from_fen("8/6p1/8/6P1/6K1/8/8/7k w - - 0 1").unwrap();
+    assert!(!board.has_non_pawn_material(board.turn));
+    let tt = TranspositionTable::new(1);
+    let result = SearchThread::new(&tt).search(&board, 6);
+    assert_eq!(
+        result.best_move.map(|mv| mv.to_string()).as_deref(),
+        Some("g4f5")
+    );
+    assert!(result.score > 1000, "Evaluación: {}", result.score);
+
+    // El mismo resultado que sin movimiento nulo
+    let tt = TranspositionTable::new(1);
+    let mut thread = SearchThread::new(&tt);
+    thread.config.null_move = false;
+    let reference = thread.search(&board, 6);
+    assert_eq!(result.best_move, reference.best_move);
+    assert_eq!(result.score, reference.score);
+}
+
+#[test]
+fn test_pruning_reaches_double_digit_depth() {
+    // Con las podas, profundidad 10 cuesta menos nodos que profundidad 6 sin
+    // ellas: es lo que permite llegar a dos cifras en partidas rápidas
+    let plain = SearchConfig {
+        null_move: false,
+        lmr: false,
+        reverse_futility: false,
+        futility: false,
+        late_move_pruning: false,
+        ..SearchConfig::default()
+    };
+
+    let shallow = total_nodes(&BENCH_FENS, 6, plain);
+    let deep = total_nodes(&BENCH_FENS, 10, SearchConfig::default());
+
+    println!(
+        "Sin podas a profundidad 6: {} nodos, con podas a profundidad 10: {} nodos",
+        shallow, deep
+    );
+    assert!(
+        deep * 2 < shallow,
+        "Profundidad 10 debería costar menos de la mitad"
+    );
+}
+
+fn search_score(fen: &str, depth: u32, config: SearchConfig) -> i32 {
+    let board = Board::from_fen(fen).expect("FEN inválido");
+    let tt = TranspositionTable::new(1);
+    let mut thread = SearchThread::new(&tt);
+    thread.config = config;
+    thread.search(&board, depth).score
+}
+
+#[test]
+fn test_extensions_find_mate_at_lower_depth() {
+    // Mate de la coz en 5 (Qb3+ Kh8 Nf7+ Kg8 Nh6+ Kh8 Qg8+ Rxg8 Nf7#): todo
+    // jaques, así que con la extensión de jaque basta la profundidad nominal 5;
+    // sin extensiones hace falta profundidad 9 para verlo
+    let fen = "5rk1/6pp/8/6N1/8/8/6PP/3Q2K1 w - - 0 1";
+    let without_extensions = || SearchConfig {
+        check_extension: false,
+        singular_extension: false,
+        ..SearchConfig::default()
+    };
+
+    assert_eq!(
+        format_score(search_score(fen, 5, SearchConfig::default())),
+        "mate 5"
+    );
+    assert!(!format_score(search_score(fen, 5, without_extensions())).starts_with("mate"));
+    assert_eq!(
+        format_score(search_score(fen, 9, without_extensions())),
+        "mate 5"
+    );
+}
+
+#[test]
+fn test_optional_extensions_keep_tactics() {
+    let config = SearchConfig {
+        recapture_extension: true,
+        passed_pawn_extension: true,
+        ..SearchConfig::default()
+    };
+    let fen = "6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1";
+    assert_eq!(format_score(search_score(fen, 3, config.clone())), "mate 1");
+
+    // El peón pasado corona
+    let board = Board::from_fen("8/8/1k6/8/8/8/5P1K/8 w - - 0 1").unwrap();
+    let tt = TranspositionTable::new(1);
+    let mut thread = SearchThread::new(&tt);
+    thread.config = config;
+    let result = thread.search(&board, 6);
+    assert_eq!(result.best_move.map(|mv| mv.from), Some(13));
+}
+
+#[test]
+fn test_multi_pv_ranks_root_moves() {
+    // Tres capturas limpias: dama, torre y caballo, en ese orden
+    let board = Board::from_fen("7k/8/8/8/1n1q2r1/P3P2P/8/4K3 w - - 0 1").unwrap();
+    let lines = search_multi_pv(&board, 4, 3);
+
+    let moves: Vec<String> = lines.iter().map(|line| line.pv[0].to_string()).collect();
+    assert_eq!(moves, ["e3d4", "h3g4", "a3b4"]);
+    assert!(lines[0].score > lines[1].score && lines[1].score > lines[2].score);
+
+    // La primera línea es la misma que da la búsqueda normal
+    assert_eq!(lines[0].pv[0], search_best_move(&board, 4).unwrap());
+}
+
+#[test]
+fn test_multi_pv_capped_by_legal_moves() {
+    // La torre controla la segunda fila: el rey solo puede ir a b1
+    let board = Board::from_fen("k7/8/8/8/8/8/7r/K7 w - - 0 1").unwrap();
+    assert_eq!(board.generate_moves().len(), 1);
+    let lines = search_multi_pv(&board, 3, 10);
+    assert_eq!(lines.len(), 1);
+}
+
+#[test]
+fn test_analyze_root_moves_scores_every_move() {
+    let board = Board::from_fen("7k/8/8/8/1n1q2r1/P3P2P/8/4K3 w - - 0 1").unwrap();
+    let analysis = analyze_root_moves(&board, 4);
+
+    let legal = board.generate_moves();
+    assert_eq!(analysis.len(), legal.len());
+    assert!(legal.iter().all(|mv| analysis.iter().any(|a| a.mv == *mv)));
+    assert!(analysis.windows(2).all(|w| w[0].score >= w[1].score));
+    assert!(analysis.iter().all(|a| a.depth == 4 && a.pv[0] == a.mv));
+
+    // La mejor coincide con la búsqueda normal
+    let tt = TranspositionTable::new(1);
+    let result = search(&board, 4, &tt);
+    assert_eq!(analysis[0].mv, result.best_move.unwrap());
+    assert_eq!(analysis[0].score, result.score);
+}
+
+#[test]
+fn test_analyze_root_moves_measures_blunder() {
+    // Qe8+ da mate en 2; Qb8+?? pierde la dama
+    let board = Board::from_fen("r5k1/5ppp/8/4Q3/8/8/8/4R1K1 w - - 0 1").unwrap();
+    let analysis = analyze_root_moves(&board, 4);
+    let score_of = |uci: &str| {
+        analysis
+            .iter()
+            .find(|a| a.mv.to_string() == uci)
+            .map(|a| a.score)
+            .unwrap()
+    };
+
+    assert_eq!(format_score(score_of("e5e8")), "mate 2");
+    // Tras Rxb8 quedan torre contra torre y tres peones negros
+    assert!(score_of("e5b8") <= -300, "Qb8+ pierde la dama");
+}
+
+#[test]
+fn test_node_limit_is_exact_and_reproducible() {
+    let board = Board::from_fen(BENCH_FENS[1]).unwrap();
+    let limits = SearchLimits {
+        nodes: Some(20_000),
+        ..Default::default()
+    };
+
+    let run = || {
+        let tt = TranspositionTable::new(16);
+        let mut thread = SearchThread::new(&tt);
+        let result = thread.search_with_limits(&board, &limits);
+        (thread.nodes, result.best_move, result.pv, result.depth)
+    };
+
+    let first = run();
+    assert_eq!(first.0, 20_000, "La búsqueda debe parar justo en el límite");
+    assert!(first.1.is_some());
+    assert_eq!(first, run());
+}
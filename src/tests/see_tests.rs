@@ -0,0 +1,44 @@
+use crate::{see::see, types::Board};
+
+fn see_of(fen: &str, mv: &str) -> i32 {
+    let board = Board::from_fen(fen).expect("FEN inválido");
+    let mv = board.parse_move(mv).expect("Movimiento ilegal");
+    see(&board, &mv)
+}
+
+#[test]
+fn test_see_free_capture() {
+    // Torre blanca captura un peón indefenso
+    assert_eq!(see_of("1k6/8/8/8/3p4/8/8/1K1R4 w - - 0 1", "d1d4"), 100);
+}
+
+#[test]
+fn test_see_defended_pawn_loses_rook() {
+    // El peón d4 está defendido por el peón e5: RxP PxR
+    assert_eq!(
+        see_of("1k6/8/8/4p3/3p4/8/8/1K1R4 w - - 0 1", "d1d4"),
+        100 - 500
+    );
+}
+
+#[test]
+fn test_see_xray_behind_attacker() {
+    // Dos torres blancas en la columna d contra torre negra defendiendo d5:
+    // RxP RxR RxR -> gana peón + torre - torre
+    let fen = "1k1r4/8/8/3p4/8/8/3R4/1K1R4 w - - 0 1";
+    assert_eq!(see_of(fen, "d2d5"), 100);
+}
+
+#[test]
+fn test_see_pawn_takes_defended_knight() {
+    // PxN es bueno aunque el caballo esté defendido
+    let fen = "1k6/8/4p3/3n4/4P3/8/8/1K6 w - - 0 1";
+    assert_eq!(see_of(fen, "e4d5"), 320 - 100);
+}
+
+#[test]
+fn test_see_quiet_move_to_attacked_square() {
+    // Mover la dama a una casilla atacada por un peón pierde la dama
+    let fen = "1k6/8/2p5/8/8/8/8/1K1Q4 w - - 0 1";
+    assert_eq!(see_of(fen, "d1d5"), -900);
+}
@@ -0,0 +1,104 @@
+use crate::{
+    search::{PvLine, search_multi_pv},
+    skill::{MAX_SKILL_LEVEL, Rng, SKILL_MULTI_PV, Skill},
+    timeman::SearchLimits,
+    types::{Board, Move},
+};
+
+fn lines(scores: &[i32]) -> Vec<PvLine> {
+    scores
+        .iter()
+        .enumerate()
+        .map(|(i, &score)| PvLine {
+            score,
+            pv: vec![Move::new(i, i + 8)],
+        })
+        .collect()
+}
+
+fn pick_counts(skill: Skill, lines: &[PvLine], seed: u64) -> Vec<usize> {
+    let mut rng = Rng::new(seed);
+    let mut counts = vec![0; lines.len()];
+    for _ in 0..1000 {
+        let mv = skill.pick_move(lines, &mut rng).unwrap();
+        counts[mv.from] += 1;
+    }
+    counts
+}
+
+#[test]
+fn test_elo_maps_to_level() {
+    assert_eq!(Skill::from_elo(800).level, 0);
+    assert_eq!(Skill::from_elo(1600).level, 10);
+    assert_eq!(Skill::from_elo(2400).level, MAX_SKILL_LEVEL);
+    assert_eq!(Skill::from_elo(5000).level, MAX_SKILL_LEVEL);
+    assert!(!Skill::from_elo(2400).enabled());
+    assert!(Skill::from_elo(2300).enabled());
+}
+
+#[test]
+fn test_limits_depth_and_nodes() {
+    let mut limits = SearchLimits::fixed_depth(12);
+    Skill::from_level(0).limit(&mut limits);
+    assert_eq!(limits.depth, Some(1));
+    assert!(limits.nodes.is_some());
+
+    // El nivel máximo no toca los límites
+    let mut limits = SearchLimits::fixed_depth(12);
+    Skill::from_level(MAX_SKILL_LEVEL).limit(&mut limits);
+    assert_eq!(limits.depth, Some(12));
+    assert_eq!(limits.nodes, None);
+}
+
+#[test]
+fn test_full_strength_always_picks_best() {
+    let lines = lines(&[50, 45, 40, 0]);
+    let counts = pick_counts(Skill::from_level(MAX_SKILL_LEVEL), &lines, 1);
+    assert_eq!(counts[0], 1000);
+}
+
+#[test]
+fn test_weak_level_prefers_small_score_gaps() {
+    let lines = lines(&[100, 0, -200, -900]);
+    let counts = pick_counts(Skill::from_level(0), &lines, 1);
+
+    // La mejor sigue siendo la favorita, pero no siempre se juega
+    assert!(counts[0] > counts[1] && counts[1] > counts[3]);
+    assert!(counts[0] < 1000);
+    // La imprecisión deliberada alcanza a -200, pero nunca regala la dama
+    assert!(counts[2] > 0 && counts[2] < 150);
+    assert_eq!(counts[3], 0);
+}
+
+#[test]
+fn test_high_level_never_blunders() {
+    // En el nivel 15 el margen es de 100 centipeones: la segunda jugada se
+    // juega a veces, la que pierde 900 nunca
+    let lines = lines(&[50, 0, -850]);
+    for seed in 1..20 {
+        let counts = pick_counts(Skill::from_level(15), &lines, seed);
+        assert_eq!(counts[2], 0, "Semilla {}", seed);
+    }
+    let counts = pick_counts(Skill::from_level(15), &lines, 1);
+    assert!(counts[1] > 0);
+}
+
+#[test]
+fn test_same_seed_same_choices() {
+    let lines = lines(&[30, 20, 10, 0]);
+    let skill = Skill::from_level(5);
+    assert_eq!(
+        pick_counts(skill, &lines, 42),
+        pick_counts(skill, &lines, 42)
+    );
+}
+
+#[test]
+fn test_limited_search_returns_legal_move() {
+    let board = Board::initial_position();
+    let lines = search_multi_pv(&board, Skill::from_level(0).max_depth(), SKILL_MULTI_PV);
+    let mv = Skill::from_level(0)
+        .pick_move(&lines, &mut Rng::new(3))
+        .unwrap();
+    assert!(board.generate_moves().contains(&mv));
+}
@@ -0,0 +1,175 @@
+use crate::{
+    search::{SearchConfig, SearchShared, SearchThread, format_score},
+    smp::search_parallel,
+    timeman::SearchLimits,
+    tt::{Bound, TranspositionTable},
+    types::{Board, Move},
+};
+use std::{
+    sync::{Arc, mpsc},
+    thread,
+    time::{Duration, Instant},
+};
+
+const MIDDLEGAME: &str = "r1bq1rk1/pp2bppp/2n2n2/2pp4/3P4/2PBPN2/PP1N1PPP/R2QK2R w KQ - 0 9";
+
+fn parallel(fen: &str, limits: &SearchLimits, threads: usize) -> (Option<Move>, i32, u64) {
+    let board = Board::from_fen(fen).unwrap();
+    let tt = TranspositionTable::new(16);
+    let result = search_parallel(
+        &board,
+        limits,
+        &tt,
+        threads,
+        SearchConfig::default(),
+        Arc::default(),
+    );
+    (result.best_move, result.score, result.nodes)
+}
+
+#[test]
+fn test_single_thread_is_deterministic() {
+    let limits = SearchLimits::fixed_depth(6);
+    let first = parallel(MIDDLEGAME, &limits, 1);
+    assert_eq!(first, parallel(MIDDLEGAME, &limits, 1));
+
+    // Y coincide con buscar directamente con un SearchThread
+    let board = Board::from_fen(MIDDLEGAME).unwrap();
+    let tt = TranspositionTable::new(16);
+    tt.new_search();
+    let result = SearchThread::new(&tt).search(&board, 6);
+    assert_eq!(first, (result.best_move, result.score, result.nodes));
+}
+
+#[test]
+fn test_parallel_search_finds_mate() {
+    let fen = "6k1/5ppp/8/8/8/8/8/4R1K1 w - - 0 1";
+    let (best_move, score, nodes) = parallel(fen, &SearchLimits::fixed_depth(6), 4);
+    assert_eq!(best_move.map(|mv| mv.to_string()), Some("e1e8".to_string()));
+    assert_eq!(format_score(score), "mate 1");
+    assert!(nodes > 0);
+}
+
+#[test]
+fn test_parallel_search_respects_movetime() {
+    // La búsqueda tiene que acabar sola: el vigilante solo la para si se pasa
+    // con mucho de los 300 ms
+    let board = Board::from_fen(MIDDLEGAME).unwrap();
+    let tt = TranspositionTable::new(16);
+    let shared = Arc::new(SearchShared::default());
+    let (done, finished) = mpsc::channel::<()>();
+    let stopper = shared.clone();
+    let watchdog = thread::spawn(move || {
+        let timed_out = finished.recv_timeout(Duration::from_secs(10)).is_err();
+        if timed_out {
+            stopper.stop();
+        }
+        timed_out
+    });
+
+    let limits = SearchLimits {
+        movetime: Some(300),
+        ..Default::default()
+    };
+    let result = search_parallel(&board, &limits, &tt, 4, SearchConfig::default(), shared);
+    done.send(()).unwrap();
+    assert!(!watchdog.join().unwrap(), "Tuvo que pararla el vigilante");
+    assert!(result.best_move.is_some());
+}
+
+#[test]
+fn test_stop_flag_stops_all_threads() {
+    // Una búsqueda sin límites solo termina si alguien activa la señal de parada
+    let board = Board::from_fen(MIDDLEGAME).unwrap();
+    let tt = TranspositionTable::new(16);
+    let shared = Arc::new(SearchShared::default());
+    let stopper = shared.clone();
+    let handle = thread::spawn(move || {
+        thread::sleep(std::time::Duration::from_millis(200));
+        stopper.stop();
+    });
+
+    let result = search_parallel(
+        &board,
+        &SearchLimits::default(),
+        &tt,
+        3,
+        SearchConfig::default(),
+        shared,
+    );
+    handle.join().unwrap();
+    assert!(result.best_move.is_some());
+}
+
+#[test]
+fn test_tt_shared_between_threads() {
+    // Varios hilos escribiendo a la vez: cada lectura devuelve una entrada
+    // coherente (la que escribió algún hilo) o nada, nunca una mezcla
+    let tt = TranspositionTable::new(1);
+    thread::scope(|scope| {
+        for id in 0..4u32 {
+            let tt = &tt;
+            scope.spawn(move || {
+                for i in 0..20_000u64 {
+                    let hash = (i % 64).wrapping_mul(0x9E37_79B9_7F4A_7C15);
+                    tt.store(hash, id + 1, id as i32 * 100, Bound::Exact, None, 0);
+                    if let Some(entry) = tt.probe(hash, 0) {
+                        assert_eq!(entry.score, (entry.depth as i32 - 1) * 100);
+                    }
+                }
+            });
+        }
+    });
+}
+
+// Devuelve el instante del ponderhit, el instante en que llegó la jugada y la jugada
+fn ponder_search(
+    limits: SearchLimits,
+    ponderhit_after: Duration,
+) -> (Instant, Instant, Option<Move>) {
+    let board = Board::from_fen(MIDDLEGAME).unwrap();
+    let tt = TranspositionTable::new(16);
+    let shared = Arc::new(SearchShared::default());
+    shared.set_pondering(true);
+
+    let gui = shared.clone();
+    let handle = thread::spawn(move || {
+        thread::sleep(ponderhit_after);
+        let ponderhit = Instant::now();
+        gui.set_pondering(false);
+        ponderhit
+    });
+
+    let result = search_parallel(&board, &limits, &tt, 1, SearchConfig::default(), shared);
+    let finished = Instant::now();
+    let ponderhit = handle.join().unwrap();
+    (ponderhit, finished, result.best_move)
+}
+
+#[test]
+fn test_ponder_waits_for_ponderhit() {
+    // Aunque la búsqueda termine antes, la jugada no se da hasta el ponderhit
+    let (ponderhit, finished, best_move) =
+        ponder_search(SearchLimits::fixed_depth(3), Duration::from_millis(300));
+    assert!(finished >= ponderhit);
+    assert!(best_move.is_some());
+}
+
+#[test]
+fn test_ponderhit_credits_elapsed_time() {
+    // Mientras se piensa en el tiempo del rival el reloj no corta; el ponderhit
+    // llega cuando ya se ha gastado el presupuesto y se juega enseguida, no
+    // tras otro segundo entero de búsqueda
+    let limits = SearchLimits {
+        movetime: Some(1000),
+        ..Default::default()
+    };
+    let (ponderhit, finished, best_move) = ponder_search(limits, Duration::from_millis(1200));
+    assert!(finished >= ponderhit);
+    assert!(
+        finished - ponderhit < Duration::from_millis(1000),
+        "Tardó {:?} tras el ponderhit",
+        finished - ponderhit
+    );
+    assert!(best_move.is_some());
+}
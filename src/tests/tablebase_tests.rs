@@ -0,0 +1,263 @@
+use crate::{
+    kpk::{KpkResult, probe_kpk},
+    search::{MATE_SCORE, SearchConfig, SearchThread, format_score, tablebase_score},
+    tablebase::{Dtm, Table, Tablebases, all_codes},
+    tt::TranspositionTable,
+    types::{Board, Color, Piece, PieceType},
+};
+use std::{
+    env, fs,
+    sync::{Arc, OnceLock},
+};
+
+// KQK, KRK y KPK con todas las tablas de las que dependen, generadas una
+// sola vez para todas las pruebas
+fn tablebases() -> &'static Tablebases {
+    static TABLEBASES: OnceLock<Tablebases> = OnceLock::new();
+    TABLEBASES.get_or_init(|| {
+        let mut tablebases = Tablebases::new();
+        for code in ["KQK", "KRK", "KPK"] {
+            tablebases.generate(code).unwrap();
+        }
+        tablebases
+    })
+}
+
+fn probe(tablebases: &Tablebases, fen: &str) -> Option<Dtm> {
+    tablebases.probe(&Board::from_fen(fen).expect("FEN inválido"))
+}
+
+// El mate más largo de la tabla, en jugadas
+fn longest_mate(tablebases: &Tablebases, code: &str) -> i32 {
+    let table = tablebases.table(code).unwrap();
+    (0..table.size())
+        .filter_map(|index| table.dtm_at(index).mate_in())
+        .max()
+        .unwrap_or(0)
+}
+
+#[test]
+fn test_known_mates() {
+    let tablebases = tablebases();
+    assert_eq!(
+        probe(tablebases, "k7/8/1K6/8/8/8/8/6Q1 w - - 0 1"),
+        Some(Dtm::Win(1))
+    );
+    assert_eq!(
+        probe(tablebases, "k7/8/1K6/8/8/8/8/7R w - - 0 1"),
+        Some(Dtm::Win(1))
+    );
+    // Ya en mate, también con los colores cambiados
+    assert_eq!(
+        probe(tablebases, "R6k/8/6K1/8/8/8/8/8 b - - 0 1"),
+        Some(Dtm::Loss(0))
+    );
+    assert_eq!(
+        probe(tablebases, "8/8/8/8/8/6k1/8/r6K w - - 0 1"),
+        Some(Dtm::Loss(0))
+    );
+    // Ahogado, y la dama colgada
+    assert_eq!(
+        probe(tablebases, "k7/2Q5/1K6/8/8/8/8/8 b - - 0 1"),
+        Some(Dtm::Draw)
+    );
+    assert_eq!(
+        probe(tablebases, "8/8/8/8/8/8/1kQ5/7K b - - 0 1"),
+        Some(Dtm::Draw)
+    );
+    // Sin tabla para este material
+    assert_eq!(probe(tablebases, "k7/8/1K6/8/8/8/8/6BN w - - 0 1"), None);
+}
+
+#[test]
+fn test_longest_mates() {
+    // Los valores conocidos: con dama, mate en 10; con torre, en 16
+    let tablebases = tablebases();
+    assert_eq!(longest_mate(tablebases, "KQK"), 10);
+    assert_eq!(longest_mate(tablebases, "KRK"), 16);
+}
+
+#[test]
+fn test_symmetric_positions_agree() {
+    let tablebases = tablebases();
+    for fen in [
+        "8/8/3k4/8/8/2K5/8/R7 w - - 0 1",
+        "8/8/3k4/8/8/2K5/8/R7 b - - 0 1",
+        "8/8/8/8/5k2/8/1R6/6K1 w - - 0 1",
+    ] {
+        let board = Board::from_fen(fen).unwrap();
+        let dtm = tablebases.probe(&board);
+        assert!(dtm.is_some());
+        // Con los colores cambiados
+        assert_eq!(tablebases.probe(&board.mirror()), dtm, "{}", fen);
+        // Reflejada y girada: ocho simetrías sin peones
+        let mut reflected = board.clone();
+        let mut transposed = board.clone();
+        for square in 0..64 {
+            reflected.squares[square ^ 7] = board.squares[square];
+            transposed.squares[square % 8 * 8 + square / 8] = board.squares[square];
+        }
+        assert_eq!(tablebases.probe(&reflected), dtm, "{}", fen);
+        assert_eq!(tablebases.probe(&transposed), dtm, "{}", fen);
+    }
+}
+
+#[test]
+fn test_agrees_with_kpk_bitbase() {
+    // Genera también las tablas de las coronaciones
+    let tablebases = tablebases();
+    assert!(tablebases.table("KNK").is_some());
+
+    let mut board = Board::from_fen("8/8/8/8/8/8/8/8 w - - 0 1").unwrap();
+    for pawn in 8..56 {
+        for white_king in 0..64 {
+            for black_king in 0..64 {
+                if pawn == white_king || pawn == black_king || white_king == black_king {
+                    continue;
+                }
+                board.squares = [None; 64];
+                board.squares[white_king] = Some(Piece::new(Color::White, PieceType::King));
+                board.squares[black_king] = Some(Piece::new(Color::Black, PieceType::King));
+                board.squares[pawn] = Some(Piece::new(Color::White, PieceType::Pawn));
+                for turn in [Color::White, Color::Black] {
+                    board.turn = turn;
+                    if board.is_king_attacked(turn.opposite()) {
+                        continue;
+                    }
+                    let expected = match probe_kpk(&board) {
+                        Some(KpkResult::Win) if turn == Color::White => "win",
+                        Some(KpkResult::Win) => "loss",
+                        _ => "draw",
+                    };
+                    let dtm = match tablebases.probe(&board) {
+                        Some(Dtm::Win(_)) => "win",
+                        Some(Dtm::Loss(_)) => "loss",
+                        _ => "draw",
+                    };
+                    assert_eq!(dtm, expected, "{}", board.to_fen());
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_save_and_load() {
+    let tablebases = tablebases();
+    let dir = env::temp_dir().join(format!("chess-bot-gm-tb-{}", std::process::id()));
+    let dir = dir.to_str().unwrap();
+    tablebases.save(dir).unwrap();
+    let loaded = Tablebases::load(dir);
+    fs::remove_dir_all(dir).unwrap();
+    let loaded = loaded.unwrap();
+
+    // Todas las tablas, también las que se generaron como dependencias
+    assert_eq!(loaded.len(), tablebases.len());
+    assert_eq!(loaded.max_men(), 3);
+    let original = tablebases.table("KRK").unwrap();
+    let table = loaded.table("KRK").unwrap();
+    assert!((0..table.size()).all(|i| table.dtm_at(i) == original.dtm_at(i)));
+
+    // Las series de ceros (posiciones imposibles y tablas) se comprimen
+    assert!(original.to_bytes().len() < original.size() * 3 / 4);
+    assert!(Tablebases::load("no/existe").is_err());
+}
+
+#[test]
+fn test_rejects_invalid_files() {
+    let tablebases = tablebases();
+    // Una tabla de tablas es casi solo ceros
+    assert!(tablebases.table("KBK").unwrap().to_bytes().len() < 64);
+    let bytes = tablebases.table("KRK").unwrap().to_bytes();
+    assert!(Table::from_bytes(&bytes).is_ok());
+    assert!(Table::from_bytes(b"").is_err());
+    assert!(Table::from_bytes(&bytes[..bytes.len() - 3]).is_err());
+    let mut wrong_version = bytes.clone();
+    wrong_version[4] = 7;
+    assert!(Table::from_bytes(&wrong_version).is_err());
+}
+
+#[test]
+fn test_all_codes() {
+    let codes = all_codes();
+    for code in ["KK", "KQK", "KPK", "KQKR", "KRKR", "KBNK", "KPKP", "KQQK"] {
+        assert!(codes.iter().any(|c| c == code), "{}", code);
+    }
+    // Cada material una sola vez, con el bando fuerte en blancas
+    assert!(!codes.iter().any(|c| c == "KKQ" || c == "KRKQ"));
+    assert!(codes.iter().all(|code| code.len() <= 4));
+}
+
+#[test]
+fn test_search_uses_tablebases() {
+    let mut krk = Tablebases::new();
+    krk.generate("KRK").unwrap();
+    let tablebases = Arc::new(krk);
+    let tt = TranspositionTable::new(1);
+    let mut thread = SearchThread::new(&tt);
+    thread.config = SearchConfig {
+        tablebases: Some(tablebases.clone()),
+        ..SearchConfig::default()
+    };
+
+    // En la raíz la jugada sale de la tabla: la del mate más corto
+    let board = Board::from_fen("8/8/3k4/8/8/2K5/8/R7 w - - 0 1").unwrap();
+    let dtm = tablebases.probe(&board).unwrap();
+    let result = thread.search(&board, 1);
+    let Dtm::Win(plies) = dtm else {
+        panic!("KRK debería ganar: {:?}", dtm)
+    };
+    assert_eq!(result.score, MATE_SCORE - plies as i32);
+    let best = result.best_move.unwrap();
+    let mut next = board.clone();
+    next.make_move(&best);
+    assert_eq!(tablebases.probe(&next), Some(Dtm::Loss(plies - 1)));
+    // La variante llega hasta el mate
+    assert_eq!(result.pv.len(), plies as usize);
+
+    // Dentro del árbol: tras capturar la dama queda KRK, que ya se sabe ganado
+    let board = Board::from_fen("8/8/3k4/8/8/2K5/3q4/3R4 w - - 0 1").unwrap();
+    let tt = TranspositionTable::new(1);
+    let mut thread = SearchThread::new(&tt);
+    thread.config.tablebases = Some(tablebases);
+    let result = thread.search(&board, 2);
+    assert_eq!(result.best_move.map(|mv| mv.to), Some(11));
+    assert!(result.score >= MATE_SCORE - 64, "{}", result.score);
+}
+
+#[test]
+fn test_tablebase_scores_stay_mates() {
+    assert_eq!(format_score(tablebase_score(Dtm::Win(1), 0)), "mate 1");
+    assert_eq!(format_score(tablebase_score(Dtm::Loss(2), 1)), "mate -1");
+    assert_eq!(tablebase_score(Dtm::Draw, 40), 0);
+
+    // Lejos de la raíz el mate pasaría de MAX_PLY: se queda en el umbral
+    assert!(format_score(tablebase_score(Dtm::Win(99), 60)).starts_with("mate "));
+    assert!(format_score(tablebase_score(Dtm::Loss(100), 60)).starts_with("mate -"));
+}
+
+#[test]
+#[ignore = "Genera KPKP y sus 20 dependencias: tarda varios minutos"]
+fn test_en_passant_changes_kpkp_result() {
+    let mut tablebases = Tablebases::new();
+    tablebases.generate("KPKP").unwrap();
+
+    // a4 ganaría si no se pudiera comer al paso: el rey negro no alcanza el
+    // peón. Pero tras bxa3 solo queda el peón negro y ya no se gana.
+    let fen = "8/8/8/8/1p6/7k/P7/K7 w - - 0 1";
+    assert_eq!(probe(&tablebases, fen), Some(Dtm::Draw));
+    let pushed = "8/8/8/8/Pp6/7k/8/K7 b - a3 0 1";
+    assert_eq!(probe(&tablebases, pushed), Some(Dtm::Draw));
+    let Some(Dtm::Loss(_)) = probe(&tablebases, "8/8/8/8/Pp6/7k/8/K7 b - - 0 1") else {
+        panic!("Sin captura al paso las negras pierden")
+    };
+
+    let board = Board::from_fen(fen).unwrap();
+    let moves = tablebases.root_moves(&board).unwrap();
+    assert_eq!(moves[0].1, Dtm::Draw);
+    let push = moves
+        .iter()
+        .find(|(mv, _)| mv.to_string() == "a2a4")
+        .unwrap();
+    assert_eq!(push.1, Dtm::Draw);
+}
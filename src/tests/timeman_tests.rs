@@ -0,0 +1,76 @@
+use crate::{
+    timeman::{SearchLimits, TimeManager},
+    types::Color,
+};
+
+#[test]
+fn test_parse_go_with_clock() {
+    let parts: Vec<&str> = "wtime 60000 btime 55000 winc 1000 binc 500 movestogo 20"
+        .split_whitespace()
+        .collect();
+    let limits = SearchLimits::parse(&parts);
+
+    assert_eq!(limits.wtime, Some(60000));
+    assert_eq!(limits.btime, Some(55000));
+    assert_eq!(limits.winc, Some(1000));
+    assert_eq!(limits.binc, Some(500));
+    assert_eq!(limits.movestogo, Some(20));
+    assert_eq!(limits.depth, None);
+    assert!(limits.is_timed());
+}
+
+#[test]
+fn test_parse_go_depth_and_movetime() {
+    let limits = SearchLimits::parse(&["depth", "7"]);
+    assert_eq!(limits.depth, Some(7));
+    assert!(!limits.is_timed());
+
+    let limits = SearchLimits::parse(&["movetime", "250"]);
+    assert_eq!(limits.movetime, Some(250));
+    assert!(limits.is_timed());
+}
+
+#[test]
+fn test_time_manager_without_clock_never_stops() {
+    let time = TimeManager::new(&SearchLimits::fixed_depth(5), Color::White);
+    assert!(!time.soft_limit_reached());
+    assert!(!time.hard_limit_reached());
+}
+
+#[test]
+fn test_time_manager_uses_own_clock() {
+    // Las negras casi no tienen tiempo: deben parar enseguida
+    let limits = SearchLimits {
+        wtime: Some(600_000),
+        btime: Some(1),
+        ..Default::default()
+    };
+    let time = TimeManager::new(&limits, Color::Black);
+    std::thread::sleep(std::time::Duration::from_millis(5));
+    assert!(time.hard_limit_reached());
+
+    let time = TimeManager::new(&limits, Color::White);
+    assert!(!time.soft_limit_reached());
+}
+
+#[test]
+fn test_parse_go_mate() {
+    let limits = SearchLimits::parse(&["mate", "3"]);
+    assert_eq!(limits.mate, Some(3));
+    assert!(!limits.is_timed());
+}
+
+#[test]
+fn test_parse_go_nodes() {
+    let limits = SearchLimits::parse(&["nodes", "5000"]);
+    assert_eq!(limits.nodes, Some(5000));
+    assert!(!limits.is_timed());
+}
+
+#[test]
+fn test_parse_go_ponder_and_infinite() {
+    let limits = SearchLimits::parse(&["ponder", "wtime", "1000", "btime", "900"]);
+    assert!(limits.ponder);
+    assert_eq!(limits.wtime, Some(1000));
+    assert!(SearchLimits::parse(&["infinite"]).infinite);
+}
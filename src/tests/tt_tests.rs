@@ -0,0 +1,94 @@
+use crate::{
+    search::{MATE_SCORE, search},
+    tt::*,
+    types::{Board, Move, PieceType},
+};
+
+#[test]
+fn test_store_and_probe() {
+    let tt = TranspositionTable::new(1);
+    let mv = Move::new(12, 28);
+    tt.store(0xDEAD_BEEF_1234_5678, 5, 42, Bound::Exact, Some(mv), 0);
+
+    let entry = tt
+        .probe(0xDEAD_BEEF_1234_5678, 0)
+        .expect("La entrada debería existir");
+    assert_eq!(entry.depth, 5);
+    assert_eq!(entry.score, 42);
+    assert_eq!(entry.bound, Bound::Exact);
+    assert_eq!(entry.best_move, Some(mv));
+
+    assert!(tt.probe(0x1111_2222_3333_4444, 0).is_none());
+}
+
+#[test]
+fn test_promotion_move_roundtrip() {
+    let tt = TranspositionTable::new(1);
+    let mv = Move::with_promotion(52, 60, PieceType::Knight);
+    tt.store(99, 1, 0, Bound::Lower, Some(mv), 0);
+    assert_eq!(tt.probe(99, 0).unwrap().best_move, Some(mv));
+}
+
+#[test]
+fn test_mate_score_ply_adjustment() {
+    let tt = TranspositionTable::new(1);
+    // Mate en 3 ply visto desde un nodo a ply 4: desde la raíz es MATE_SCORE - 7
+    let score = MATE_SCORE - 7;
+    tt.store(7, 3, score, Bound::Exact, None, 4);
+
+    // Al leerlo desde ply 2 el mate está 2 ply más cerca de la raíz
+    assert_eq!(tt.probe(7, 2).unwrap().score, MATE_SCORE - 5);
+    assert_eq!(score_from_tt(score_to_tt(-score, 4), 4), -score);
+    // Las puntuaciones normales no se tocan
+    assert_eq!(score_to_tt(150, 10), 150);
+}
+
+#[test]
+fn test_keeps_previous_move_when_none() {
+    let tt = TranspositionTable::new(1);
+    let mv = Move::new(1, 18);
+    tt.store(1234, 2, 10, Bound::Exact, Some(mv), 0);
+    tt.store(1234, 3, -5, Bound::Upper, None, 0);
+
+    let entry = tt.probe(1234, 0).unwrap();
+    assert_eq!(entry.bound, Bound::Upper);
+    assert_eq!(entry.best_move, Some(mv));
+}
+
+#[test]
+fn test_hashfull_resize_and_clear() {
+    let mut tt = TranspositionTable::new(1);
+    assert_eq!(tt.hashfull(), 0);
+
+    for i in 0..200_000u64 {
+        tt.store(
+            i.wrapping_mul(0x9E37_79B9_7F4A_7C15),
+            1,
+            0,
+            Bound::Exact,
+            None,
+            0,
+        );
+    }
+    assert!(tt.hashfull() > 500, "La tabla debería estar casi llena");
+
+    tt.clear();
+    assert_eq!(tt.hashfull(), 0);
+
+    tt.store(42, 1, 0, Bound::Exact, None, 0);
+    tt.resize(2);
+    assert!(tt.probe(42, 0).is_none(), "Redimensionar vacía la tabla");
+}
+
+#[test]
+fn test_search_fills_tt() {
+    let board = Board::initial_position();
+    let tt = TranspositionTable::new(1);
+    let result = search(&board, 3, &tt);
+
+    let entry = tt
+        .probe(board.hash, 0)
+        .expect("La raíz debe quedar en la tabla");
+    assert_eq!(entry.best_move, result.best_move);
+    assert!(tt.hashfull() > 0);
+}
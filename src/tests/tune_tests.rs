@@ -0,0 +1,171 @@
+use crate::{
+    evaluation::{Score, coefficients, evaluate, game_phase},
+    params::{EvalParams, NUM_PARAMS, PARAMS, idx},
+    tune::{Tuner, TuningPosition, parse_position, parse_result, sigmoid},
+    types::Board,
+};
+
+const FENS: [&str; 5] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+    "r1bq1rk1/pp3ppp/2n2n2/3p4/3P4/2NB1N2/PP3PPP/R2Q1RK1 w - - 0 1",
+    "6k1/5p1p/8/8/5n2/6q1/5P1P/6K1 b - - 0 1",
+];
+
+// Posiciones con el resultado que sugiere el material
+fn labeled_positions() -> Vec<TuningPosition> {
+    [
+        ("4k3/8/8/8/8/8/8/3QK3 w - - 0 1", 1.0),
+        ("4k3/8/8/8/8/8/8/3RK3 w - - 0 1", 1.0),
+        ("3qk3/8/8/8/8/8/8/4K3 w - - 0 1", 0.0),
+        ("3rk3/8/8/8/8/8/8/4K3 b - - 0 1", 0.0),
+        ("4k3/8/8/8/8/8/8/3NK3 w - - 0 1", 0.5),
+        ("4k3/4p3/8/8/8/8/4P3/4K3 w - - 0 1", 0.5),
+        ("4k3/8/8/8/8/8/3PP3/4K3 w - - 0 1", 1.0),
+        ("4k3/3pp3/8/8/8/8/8/4K3 b - - 0 1", 0.0),
+    ]
+    .iter()
+    .map(|(fen, result)| TuningPosition::new(&Board::from_fen(fen).unwrap(), *result))
+    .collect()
+}
+
+#[test]
+fn test_rust_source_round_trip() {
+    // El archivo de valores es exactamente lo que escribiría el ajuste
+    assert_eq!(PARAMS.rust_source(), include_str!("../eval_values.rs"));
+}
+
+#[test]
+fn test_evaluation_is_linear_in_params() {
+    for fen in FENS {
+        let board = Board::from_fen(fen).unwrap();
+        let counts = coefficients(&board).counts;
+        let total = counts
+            .iter()
+            .zip(PARAMS.values.iter())
+            .fold(Score::ZERO, |total, (count, value)| total + *value * *count);
+        assert_eq!(total.taper(game_phase(&board)), evaluate(&board), "{}", fen);
+    }
+}
+
+#[test]
+fn test_tuner_matches_evaluate() {
+    let tuner = Tuner::new(Vec::new(), &PARAMS);
+    for fen in FENS {
+        let board = Board::from_fen(fen).unwrap();
+        let position = TuningPosition::new(&board, 0.5);
+        // Sin el redondeo de la mezcla por fase, como mucho 1 cp de diferencia
+        let diff = tuner.evaluate(&position) - evaluate(&board) as f64;
+        assert!(diff.abs() < 1.0, "{}: {}", fen, diff);
+    }
+    assert_eq!(tuner.to_params(), PARAMS);
+}
+
+#[test]
+fn test_parse_result() {
+    assert_eq!(parse_result("1-0"), Some(1.0));
+    assert_eq!(parse_result("\"0-1\";"), Some(0.0));
+    assert_eq!(parse_result("[0.5]"), Some(0.5));
+    assert_eq!(parse_result("1/2-1/2"), Some(0.5));
+    assert_eq!(parse_result("[1.0]"), Some(1.0));
+    assert_eq!(parse_result("*"), None);
+    // El número de jugada del FEN no es un resultado
+    assert_eq!(parse_result("1"), None);
+}
+
+#[test]
+fn test_parse_position_formats() {
+    let lines = [
+        "4k3/4p3/8/8/8/8/4P3/3QK3 w - - 0 1 [1.0]",
+        "4k3/4p3/8/8/8/8/4P3/3QK3 w - - c9 \"1-0\";",
+        "4k3/4p3/8/8/8/8/4P3/3QK3 w - - 0 1 | 1-0",
+    ];
+    for line in lines {
+        let position = parse_position(line).expect(line);
+        assert_eq!(position.result, 1.0);
+        assert_eq!(position.phase, 4);
+    }
+
+    // Sin resultado, con el rey en jaque, sin FEN o en un final conocido no
+    // se usan
+    assert!(parse_position("4k3/4p3/8/8/8/8/4P3/3QK3 w - - 0 1").is_none());
+    assert!(parse_position("4k3/4p3/8/8/8/8/4P3/3QK3 w - - 0 1 [*]").is_none());
+    assert!(parse_position("3Qk3/4p3/8/8/8/8/4P3/4K3 b - - 0 1 [1.0]").is_none());
+    assert!(parse_position("").is_none());
+    assert!(parse_position("4k3/8/8/8/8/8/8/3QK3 w - - 0 1 [1.0]").is_none());
+}
+
+#[test]
+fn test_sigmoid() {
+    assert_eq!(sigmoid(0.0, 1.0), 0.5);
+    assert!((sigmoid(400.0, 1.0) - 10.0 / 11.0).abs() < 1e-12);
+    assert!((sigmoid(-250.0, 1.3) + sigmoid(250.0, 1.3) - 1.0).abs() < 1e-12);
+}
+
+#[test]
+fn test_fit_k_minimizes_error() {
+    let mut tuner = Tuner::new(labeled_positions(), &PARAMS);
+    let k = tuner.fit_k();
+    assert!(k > 0.0 && k < 10.0);
+    let best = tuner.error(k);
+    assert!(best <= tuner.error(k * 0.8));
+    assert!(best <= tuner.error(k * 1.2));
+}
+
+#[test]
+fn test_tunable_components() {
+    // El rey no tiene valor material
+    assert!(!EvalParams::is_tunable(idx::MATERIAL + 5, 0));
+    assert!(!EvalParams::is_tunable(idx::MATERIAL + 5, 1));
+    // La seguridad del rey es solo de medio juego
+    assert!(EvalParams::is_tunable(idx::SAFETY_TABLE + 10, 0));
+    assert!(!EvalParams::is_tunable(idx::SAFETY_TABLE + 10, 1));
+    assert!(!EvalParams::is_tunable(idx::SHIELD_MISSING, 1));
+    // Las distancias de los reyes a los pasados, solo de final
+    assert!(!EvalParams::is_tunable(idx::PASSED_ENEMY_KING_DISTANCE, 0));
+    assert!(EvalParams::is_tunable(idx::PASSED_ENEMY_KING_DISTANCE, 1));
+    assert!(EvalParams::is_tunable(idx::MOBILITY + 1, 0));
+    assert!(EvalParams::is_tunable(idx::BISHOP_PAIR, 1));
+}
+
+#[test]
+fn test_adam_reduces_error() {
+    let mut tuner = Tuner::new(labeled_positions(), &PARAMS);
+    let k = tuner.fit_k();
+    let initial = tuner.error(k);
+    let mut reports = 0;
+    let error = tuner.adam(100, 1.0, |_, _| reports += 1);
+    assert!(error < initial, "{} >= {}", error, initial);
+    assert_eq!(reports, 2);
+
+    // Lo que no se puede ajustar se queda igual
+    for index in 0..NUM_PARAMS {
+        for phase in 0..2 {
+            if !EvalParams::is_tunable(index, phase) {
+                let value = PARAMS.values[index];
+                let original = [value.mg, value.eg][phase] as f64;
+                assert_eq!(tuner.params[index][phase], original);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_local_search_reduces_error() {
+    let mut tuner = Tuner::new(labeled_positions(), &PARAMS);
+    let k = tuner.fit_k();
+    let initial = tuner.error(k);
+    let error = tuner.local_search(3, |_, _| {});
+    assert!(error < initial);
+    // El error que lleva la búsqueda coincide con el recalculado
+    assert!((error - tuner.error(k)).abs() < 1e-9);
+    // Los valores siguen siendo enteros
+    assert!(
+        tuner
+            .params
+            .iter()
+            .flatten()
+            .all(|value| value.fract() == 0.0)
+    );
+}
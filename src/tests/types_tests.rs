@@ -19,3 +19,15 @@ fn test_castling_rights_from_fen_white_king_side_black_queen_side() {
     assert!(!rights.black_kingside);
     assert!(rights.black_queenside);
 }
+
+#[test]
+fn test_relative_rank_and_distance() {
+    // e2 es la segunda fila de las blancas y la séptima de las negras
+    assert_eq!(relative_rank(12, Color::White), 1);
+    assert_eq!(relative_rank(12, Color::Black), 6);
+
+    assert_eq!(distance(0, 63), 7); // a1-h8
+    assert_eq!(distance(12, 28), 2); // e2-e4
+    assert_eq!(distance(12, 21), 1); // e2-f3
+    assert_eq!(distance(12, 12), 0);
+}
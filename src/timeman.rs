@@ -0,0 +1,114 @@
+use crate::types::Color;
+use std::time::{Duration, Instant};
+
+// Jugadas que suponemos que quedan cuando la GUI no manda "movestogo"
+const DEFAULT_MOVES_TO_GO: u64 = 25;
+
+// Límites de una búsqueda, tal y como llegan en el comando "go"
+#[derive(Debug, Clone, Default)]
+pub struct SearchLimits {
+    pub depth: Option<u32>,
+    pub movetime: Option<u64>,
+    pub wtime: Option<u64>,
+    pub btime: Option<u64>,
+    pub winc: Option<u64>,
+    pub binc: Option<u64>,
+    pub movestogo: Option<u64>,
+    pub nodes: Option<u64>,
+    // "go ponder" y "go infinite": no se da la jugada hasta "ponderhit" o "stop"
+    pub ponder: bool,
+    pub infinite: bool,
+    // "go mate N": buscar solo un mate en N jugadas
+    pub mate: Option<u32>,
+    // Margen (en ms) que reservamos para la latencia de la GUI o de la red
+    pub move_overhead: u64,
+}
+
+impl SearchLimits {
+    pub fn fixed_depth(depth: u32) -> Self {
+        SearchLimits {
+            depth: Some(depth),
+            ..Default::default()
+        }
+    }
+
+    // Lee los parámetros de "go wtime 1000 btime 1000 winc 0 ..."
+    pub fn parse(parts: &[&str]) -> Self {
+        let mut limits = SearchLimits::default();
+        let mut tokens = parts.iter();
+
+        while let Some(&token) = tokens.next() {
+            let mut value = || tokens.next().and_then(|v| v.parse::<u64>().ok());
+            match token {
+                "depth" => limits.depth = value().map(|d| d as u32),
+                "movetime" => limits.movetime = value(),
+                "wtime" => limits.wtime = value(),
+                "btime" => limits.btime = value(),
+                "winc" => limits.winc = value(),
+                "binc" => limits.binc = value(),
+                "movestogo" => limits.movestogo = value(),
+                "nodes" => limits.nodes = value(),
+                "mate" => limits.mate = value().map(|n| n as u32),
+                "ponder" => limits.ponder = true,
+                "infinite" => limits.infinite = true,
+                _ => {}
+            }
+        }
+
+        limits
+    }
+
+    pub fn is_timed(&self) -> bool {
+        self.movetime.is_some() || self.wtime.is_some() || self.btime.is_some()
+    }
+}
+
+// Decide cuánto tiempo dedicar a una jugada. El límite blando indica cuándo
+// no merece la pena empezar otra iteración; el duro, cuándo abortar la actual.
+pub struct TimeManager {
+    start: Instant,
+    soft_limit: Option<Duration>,
+    hard_limit: Option<Duration>,
+}
+
+impl TimeManager {
+    pub fn new(limits: &SearchLimits, turn: Color) -> Self {
+        let overhead = limits.move_overhead;
+        let (time, increment) = match turn {
+            Color::White => (limits.wtime, limits.winc.unwrap_or(0)),
+            Color::Black => (limits.btime, limits.binc.unwrap_or(0)),
+        };
+
+        let (soft_limit, hard_limit) = if let Some(movetime) = limits.movetime {
+            let budget = movetime.saturating_sub(overhead).max(1);
+            (Some(budget), Some(budget))
+        } else if let Some(time) = time {
+            let time_left = time.saturating_sub(overhead).max(1);
+            let moves_to_go = limits.movestogo.unwrap_or(DEFAULT_MOVES_TO_GO).max(1);
+            let optimum = time_left / moves_to_go + increment * 3 / 4;
+            // Nunca gastamos más de la mitad del reloj en una sola jugada
+            let hard = (optimum * 3).min(time_left / 2).max(1);
+            (Some(optimum.min(hard)), Some(hard))
+        } else {
+            (None, None)
+        };
+
+        TimeManager {
+            start: Instant::now(),
+            soft_limit: soft_limit.map(Duration::from_millis),
+            hard_limit: hard_limit.map(Duration::from_millis),
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    pub fn soft_limit_reached(&self) -> bool {
+        self.soft_limit.is_some_and(|limit| self.elapsed() >= limit)
+    }
+
+    pub fn hard_limit_reached(&self) -> bool {
+        self.hard_limit.is_some_and(|limit| self.elapsed() >= limit)
+    }
+}
@@ -0,0 +1,288 @@
+use crate::{
+    search::MATE_BOUND,
+    types::{Move, PieceType},
+};
+use std::sync::atomic::{AtomicU8, AtomicU64, Ordering};
+
+const BUCKET_SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Bound {
+    Exact,
+    // La puntuación real es >= a la guardada (fail-high)
+    Lower,
+    // La puntuación real es <= a la guardada (fail-low)
+    Upper,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TTData {
+    pub depth: u32,
+    pub score: i32,
+    pub bound: Bound,
+    pub best_move: Option<Move>,
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct TTEntry {
+    best_move: u16,
+    depth: u8,
+    // 2 bits para el tipo de cota y 6 para la edad (0 = entrada vacía)
+    bound_age: u8,
+    score: i32,
+}
+
+impl TTEntry {
+    fn pack(&self) -> u64 {
+        self.best_move as u64
+            | (self.depth as u64) << 16
+            | (self.bound_age as u64) << 24
+            | (self.score as u32 as u64) << 32
+    }
+
+    fn unpack(data: u64) -> Self {
+        TTEntry {
+            best_move: data as u16,
+            depth: (data >> 16) as u8,
+            bound_age: (data >> 24) as u8,
+            score: (data >> 32) as u32 as i32,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.bound_age == 0
+    }
+
+    fn age(&self) -> u8 {
+        self.bound_age >> 2
+    }
+
+    fn bound(&self) -> Bound {
+        match self.bound_age & 0b11 {
+            1 => Bound::Exact,
+            2 => Bound::Lower,
+            _ => Bound::Upper,
+        }
+    }
+}
+
+// Una entrada de la tabla compartida entre hilos, sin cerrojos: se guarda la
+// clave XOR los datos, así una entrada a medio escribir por otro hilo no
+// coincide con ninguna clave y simplemente se ignora.
+#[derive(Debug, Default)]
+struct AtomicEntry {
+    key_xor_data: AtomicU64,
+    data: AtomicU64,
+}
+
+impl AtomicEntry {
+    // Devuelve la clave y la entrada; la clave solo es válida si no hubo carrera
+    fn load(&self) -> (u64, TTEntry) {
+        let data = self.data.load(Ordering::Relaxed);
+        let key = self.key_xor_data.load(Ordering::Relaxed) ^ data;
+        (key, TTEntry::unpack(data))
+    }
+
+    fn save(&self, key: u64, entry: &TTEntry) {
+        let data = entry.pack();
+        self.key_xor_data.store(key ^ data, Ordering::Relaxed);
+        self.data.store(data, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug, Default)]
+struct Bucket {
+    entries: [AtomicEntry; BUCKET_SIZE],
+}
+
+// Tabla de transposición. Los hilos de búsqueda la comparten por referencia:
+// leer y guardar no necesitan acceso exclusivo.
+pub struct TranspositionTable {
+    buckets: Vec<Bucket>,
+    age: AtomicU8,
+}
+
+impl TranspositionTable {
+    pub fn new(size_mb: usize) -> Self {
+        let mut tt = TranspositionTable {
+            buckets: Vec::new(),
+            age: AtomicU8::new(1),
+        };
+        tt.resize(size_mb);
+        tt
+    }
+
+    // Cambia el tamaño de la tabla (en MB). Se pierde todo su contenido.
+    pub fn resize(&mut self, size_mb: usize) {
+        let bytes = size_mb.max(1) * 1024 * 1024;
+        let count = (bytes / std::mem::size_of::<Bucket>()).max(1);
+        self.buckets = (0..count).map(|_| Bucket::default()).collect();
+        *self.age.get_mut() = 1;
+    }
+
+    pub fn clear(&mut self) {
+        self.buckets.fill_with(Bucket::default);
+        *self.age.get_mut() = 1;
+    }
+
+    // Se llama al empezar cada búsqueda para que las entradas viejas se reemplacen antes
+    pub fn new_search(&self) {
+        // La edad ocupa 6 bits y nunca vale 0 (reservado para entradas vacías)
+        let age = self.age();
+        self.age.store(age % 63 + 1, Ordering::Relaxed);
+    }
+
+    fn age(&self) -> u8 {
+        self.age.load(Ordering::Relaxed)
+    }
+
+    fn bucket_index(&self, hash: u64) -> usize {
+        ((hash as u128 * self.buckets.len() as u128) >> 64) as usize
+    }
+
+    pub fn probe(&self, hash: u64, ply: i32) -> Option<TTData> {
+        let bucket = &self.buckets[self.bucket_index(hash)];
+
+        bucket
+            .entries
+            .iter()
+            .map(AtomicEntry::load)
+            .find(|(key, entry)| !entry.is_empty() && *key == hash)
+            .map(|(_, entry)| TTData {
+                depth: entry.depth as u32,
+                score: score_from_tt(entry.score, ply),
+                bound: entry.bound(),
+                best_move: unpack_move(entry.best_move),
+            })
+    }
+
+    pub fn store(
+        &self,
+        hash: u64,
+        depth: u32,
+        score: i32,
+        bound: Bound,
+        best_move: Option<Move>,
+        ply: i32,
+    ) {
+        let age = self.age();
+        let bucket = &self.buckets[self.bucket_index(hash)];
+        let entries: [(u64, TTEntry); BUCKET_SIZE] =
+            std::array::from_fn(|slot| bucket.entries[slot].load());
+
+        // Si la posición ya está en el cubo reutilizamos su hueco; si no,
+        // reemplazamos la entrada menos valiosa (vacía, vieja o poco profunda).
+        let (slot, previous_move) = match entries
+            .iter()
+            .position(|(key, entry)| !entry.is_empty() && *key == hash)
+        {
+            Some(slot) => {
+                let entry = &entries[slot].1;
+                // No pisamos un resultado mucho más profundo de esta misma búsqueda
+                if bound != Bound::Exact && entry.age() == age && depth + 3 < entry.depth as u32 {
+                    return;
+                }
+                (slot, entry.best_move)
+            }
+            None => {
+                let slot = (0..BUCKET_SIZE)
+                    .min_by_key(|&slot| replacement_value(&entries[slot].1, age))
+                    .unwrap();
+                (slot, 0)
+            }
+        };
+
+        let packed_move = match best_move {
+            Some(mv) => pack_move(&mv),
+            // Conservamos la mejor jugada anterior si esta búsqueda no encontró ninguna
+            None => previous_move,
+        };
+        let bound_bits = match bound {
+            Bound::Exact => 1,
+            Bound::Lower => 2,
+            Bound::Upper => 3,
+        };
+
+        let entry = TTEntry {
+            best_move: packed_move,
+            depth: depth.min(u8::MAX as u32) as u8,
+            bound_age: (age << 2) | bound_bits,
+            score: score_to_tt(score, ply),
+        };
+        bucket.entries[slot].save(hash, &entry);
+    }
+
+    // Porcentaje de ocupación en tanto por mil, como pide UCI
+    pub fn hashfull(&self) -> usize {
+        let age = self.age();
+        let sample = self.buckets.len().min(1000 / BUCKET_SIZE);
+        let used: usize = self.buckets[..sample]
+            .iter()
+            .flat_map(|bucket| bucket.entries.iter())
+            .map(|entry| entry.load().1)
+            .filter(|entry| !entry.is_empty() && entry.age() == age)
+            .count();
+        used * 1000 / (sample * BUCKET_SIZE)
+    }
+}
+
+fn replacement_value(entry: &TTEntry, age: u8) -> i32 {
+    if entry.is_empty() {
+        return i32::MIN;
+    }
+    let age_distance = (age as i32 - entry.age() as i32).rem_euclid(63);
+    entry.depth as i32 - 8 * age_distance
+}
+
+// Las puntuaciones de mate se guardan relativas al nodo y no a la raíz,
+// porque la misma posición se puede alcanzar a distintas distancias.
+pub fn score_to_tt(score: i32, ply: i32) -> i32 {
+    if score >= MATE_BOUND {
+        score + ply
+    } else if score <= -MATE_BOUND {
+        score - ply
+    } else {
+        score
+    }
+}
+
+pub fn score_from_tt(score: i32, ply: i32) -> i32 {
+    if score >= MATE_BOUND {
+        score - ply
+    } else if score <= -MATE_BOUND {
+        score + ply
+    } else {
+        score
+    }
+}
+
+fn pack_move(mv: &Move) -> u16 {
+    let promotion = match mv.promotion {
+        Some(PieceType::Knight) => 1,
+        Some(PieceType::Bishop) => 2,
+        Some(PieceType::Rook) => 3,
+        Some(PieceType::Queen) => 4,
+        _ => 0,
+    };
+    (mv.from as u16) | (mv.to as u16) << 6 | promotion << 12
+}
+
+fn unpack_move(packed: u16) -> Option<Move> {
+    if packed == 0 {
+        return None;
+    }
+    let from = (packed & 0x3F) as usize;
+    let to = ((packed >> 6) & 0x3F) as usize;
+    let promotion = match packed >> 12 {
+        1 => Some(PieceType::Knight),
+        2 => Some(PieceType::Bishop),
+        3 => Some(PieceType::Rook),
+        4 => Some(PieceType::Queen),
+        _ => None,
+    };
+    Some(Move {
+        from,
+        to,
+        promotion,
+    })
+}
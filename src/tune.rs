@@ -0,0 +1,350 @@
+use crate::{
+    endgame,
+    evaluation::{MAX_PHASE, Score, coefficients, game_phase},
+    params::{EvalParams, NUM_PARAMS, PARAMS},
+    types::Board,
+};
+use std::{fs, time::Instant};
+
+pub const DEFAULT_EPOCHS: usize = 500;
+pub const DEFAULT_OUTPUT: &str = "eval_values.rs";
+
+// Adam con los parámetros en centipeones: cada paso mueve como mucho ~1 cp
+pub const ADAM_LEARNING_RATE: f64 = 1.0;
+const ADAM_BETA1: f64 = 0.9;
+const ADAM_BETA2: f64 = 0.999;
+const ADAM_EPSILON: f64 = 1e-8;
+
+// Cada cuántas épocas se informa del error
+const REPORT_INTERVAL: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    // Descenso por gradiente con Adam: rápido, mueve todos los parámetros a la vez
+    Adam,
+    // La búsqueda local del Texel original: prueba ±1 en cada parámetro
+    LocalSearch,
+}
+
+// Posición de entrenamiento: qué parámetros usa y cuántas veces (desde las
+// blancas), su fase y el resultado de la partida para las blancas (1, ½ o 0).
+// Se guarda solo lo que no es cero: unas decenas de parámetros de cientos.
+#[derive(Debug, Clone)]
+pub struct TuningPosition {
+    pub coefficients: Vec<(u16, i16)>,
+    pub phase: i32,
+    pub result: f64,
+}
+
+impl TuningPosition {
+    pub fn new(board: &Board, result: f64) -> Self {
+        let coefficients = coefficients(board)
+            .counts
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count != 0)
+            .map(|(index, count)| (index as u16, *count as i16))
+            .collect();
+        TuningPosition {
+            coefficients,
+            phase: game_phase(board),
+            result,
+        }
+    }
+
+    // Peso del medio juego y del final en la mezcla por fase
+    fn weights(&self) -> [f64; 2] {
+        let mg = self.phase as f64 / MAX_PHASE as f64;
+        [mg, 1.0 - mg]
+    }
+}
+
+// Resultado de la partida en la notación de PGN o como número
+pub fn parse_result(token: &str) -> Option<f64> {
+    let token = token.trim_matches(|c: char| "[]();\"|,".contains(c));
+    match token {
+        "1-0" | "1.0" => Some(1.0),
+        "0-1" | "0.0" => Some(0.0),
+        "1/2-1/2" | "0.5" => Some(0.5),
+        _ => None,
+    }
+}
+
+// Una línea del archivo: el FEN (basta con las cuatro primeras partes) y el
+// resultado al final, p. ej. `<fen> [1.0]`, `<fen> c9 "1/2-1/2";` o `<fen> | 0-1`.
+// Las posiciones en jaque no son tranquilas y se descartan, y los finales
+// conocidos tampoco sirven: no se evalúan con los parámetros.
+pub fn parse_position(line: &str) -> Option<TuningPosition> {
+    let tokens: Vec<&str> = line.split_whitespace().collect();
+    if tokens.len() < 5 {
+        return None;
+    }
+    let result = parse_result(tokens[tokens.len() - 1])?;
+    let board = Board::from_fen(&tokens[..4].join(" ")).ok()?;
+    if board.is_king_attacked(board.turn) || endgame::probe(&board).is_some() {
+        return None;
+    }
+    Some(TuningPosition::new(&board, result))
+}
+
+pub fn load_positions(path: &str) -> Result<Vec<TuningPosition>, String> {
+    let text = fs::read_to_string(path).map_err(|e| format!("No se pudo leer {}: {}", path, e))?;
+    Ok(text.lines().filter_map(parse_position).collect())
+}
+
+// Probabilidad de ganar (con las tablas como medio punto) que predice una
+// evaluación en centipeones
+pub fn sigmoid(eval: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * eval / 400.0))
+}
+
+// Ajuste de Texel: busca los parámetros con los que la evaluación, pasada
+// por la sigmoide, mejor predice el resultado de las partidas
+pub struct Tuner {
+    positions: Vec<TuningPosition>,
+    // [medio juego, final] de cada parámetro, con decimales mientras se ajusta
+    pub params: Vec<[f64; 2]>,
+    pub k: f64,
+}
+
+impl Tuner {
+    pub fn new(positions: Vec<TuningPosition>, params: &EvalParams) -> Self {
+        Tuner {
+            positions,
+            params: params
+                .values
+                .iter()
+                .map(|value| [value.mg as f64, value.eg as f64])
+                .collect(),
+            k: 1.0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    // Medio juego y final de la posición, sin mezclar
+    fn phase_scores(&self, position: &TuningPosition) -> [f64; 2] {
+        let mut scores = [0.0; 2];
+        for &(index, count) in &position.coefficients {
+            let param = self.params[index as usize];
+            scores[0] += param[0] * count as f64;
+            scores[1] += param[1] * count as f64;
+        }
+        scores
+    }
+
+    pub fn evaluate(&self, position: &TuningPosition) -> f64 {
+        let [mg, eg] = self.phase_scores(position);
+        let [mg_weight, eg_weight] = position.weights();
+        mg * mg_weight + eg * eg_weight
+    }
+
+    // Error cuadrático medio entre resultado y predicción
+    pub fn error(&self, k: f64) -> f64 {
+        let total: f64 = self
+            .positions
+            .iter()
+            .map(|position| (position.result - sigmoid(self.evaluate(position), k)).powi(2))
+            .sum();
+        total / self.positions.len().max(1) as f64
+    }
+
+    // K convierte centipeones en probabilidad y depende de la escala de la
+    // evaluación: se fija antes de ajustar, con los parámetros de partida,
+    // minimizando el error por sección áurea
+    pub fn fit_k(&mut self) -> f64 {
+        let ratio = (5f64.sqrt() - 1.0) / 2.0;
+        let (mut low, mut high) = (0.0, 10.0);
+        while high - low > 1e-4 {
+            let a = high - ratio * (high - low);
+            let b = low + ratio * (high - low);
+            if self.error(a) < self.error(b) {
+                high = b;
+            } else {
+                low = a;
+            }
+        }
+        self.k = (low + high) / 2.0;
+        self.k
+    }
+
+    // Derivada del error respecto a cada componente de cada parámetro
+    fn gradient(&self) -> Vec<[f64; 2]> {
+        let mut gradient = vec![[0.0; 2]; NUM_PARAMS];
+        let scale = self.k * 10f64.ln() / 400.0;
+        for position in &self.positions {
+            let prediction = sigmoid(self.evaluate(position), self.k);
+            let delta = -2.0 * (position.result - prediction) * prediction * (1.0 - prediction);
+            let [mg_weight, eg_weight] = position.weights();
+            for &(index, count) in &position.coefficients {
+                let term = delta * scale * count as f64;
+                gradient[index as usize][0] += term * mg_weight;
+                gradient[index as usize][1] += term * eg_weight;
+            }
+        }
+        let n = self.positions.len().max(1) as f64;
+        for component in gradient.iter_mut().flatten() {
+            *component /= n;
+        }
+        gradient
+    }
+
+    // Descenso por gradiente con Adam sobre todas las posiciones a la vez.
+    // `report` recibe la época y el error cada REPORT_INTERVAL épocas.
+    pub fn adam(
+        &mut self,
+        epochs: usize,
+        learning_rate: f64,
+        mut report: impl FnMut(usize, f64),
+    ) -> f64 {
+        let mut momentum = vec![[0.0; 2]; NUM_PARAMS];
+        let mut velocity = vec![[0.0; 2]; NUM_PARAMS];
+
+        for epoch in 1..=epochs {
+            let gradient = self.gradient();
+            let correction1 = 1.0 - ADAM_BETA1.powi(epoch as i32);
+            let correction2 = 1.0 - ADAM_BETA2.powi(epoch as i32);
+
+            for index in 0..NUM_PARAMS {
+                for phase in 0..2 {
+                    if !EvalParams::is_tunable(index, phase) {
+                        continue;
+                    }
+                    let g = gradient[index][phase];
+                    let m = &mut momentum[index][phase];
+                    let v = &mut velocity[index][phase];
+                    *m = ADAM_BETA1 * *m + (1.0 - ADAM_BETA1) * g;
+                    *v = ADAM_BETA2 * *v + (1.0 - ADAM_BETA2) * g * g;
+                    let step = learning_rate * (*m / correction1)
+                        / ((*v / correction2).sqrt() + ADAM_EPSILON);
+                    self.params[index][phase] -= step;
+                }
+            }
+
+            if epoch % REPORT_INTERVAL == 0 || epoch == epochs {
+                report(epoch, self.error(self.k));
+            }
+        }
+        self.error(self.k)
+    }
+
+    // Búsqueda local: prueba a subir o bajar 1 cada componente y se queda con
+    // el cambio si baja el error. Repite pasadas hasta que nada mejora.
+    // Cada cambio solo recalcula las posiciones en las que aparece el
+    // parámetro.
+    pub fn local_search(&mut self, max_passes: usize, mut report: impl FnMut(usize, f64)) -> f64 {
+        for param in self.params.iter_mut().flatten() {
+            *param = param.round();
+        }
+
+        let mut users: Vec<Vec<(usize, f64)>> = vec![Vec::new(); NUM_PARAMS];
+        for (i, position) in self.positions.iter().enumerate() {
+            for &(index, count) in &position.coefficients {
+                users[index as usize].push((i, count as f64));
+            }
+        }
+        let mut evals: Vec<f64> = self.positions.iter().map(|p| self.evaluate(p)).collect();
+        let n = self.positions.len().max(1) as f64;
+        let k = self.k;
+        let squared =
+            |position: &TuningPosition, eval: f64| (position.result - sigmoid(eval, k)).powi(2);
+        let mut total: f64 = self
+            .positions
+            .iter()
+            .zip(&evals)
+            .map(|(position, &eval)| squared(position, eval))
+            .sum();
+
+        for pass in 1..=max_passes {
+            let mut improved = false;
+            for (index, users) in users.iter().enumerate() {
+                for phase in 0..2 {
+                    if !EvalParams::is_tunable(index, phase) || users.is_empty() {
+                        continue;
+                    }
+                    for delta in [1.0, -1.0] {
+                        let change: f64 = users
+                            .iter()
+                            .map(|&(i, count)| {
+                                let position = &self.positions[i];
+                                let step = delta * count * position.weights()[phase];
+                                squared(position, evals[i] + step) - squared(position, evals[i])
+                            })
+                            .sum();
+                        if change < -1e-12 {
+                            for &(i, count) in users {
+                                evals[i] += delta * count * self.positions[i].weights()[phase];
+                            }
+                            self.params[index][phase] += delta;
+                            total += change;
+                            improved = true;
+                            break;
+                        }
+                    }
+                }
+            }
+            report(pass, total / n);
+            if !improved {
+                break;
+            }
+        }
+        total / n
+    }
+
+    // Parámetros redondeados, listos para la evaluación
+    pub fn to_params(&self) -> EvalParams {
+        let mut params = PARAMS.clone();
+        for (value, param) in params.values.iter_mut().zip(&self.params) {
+            *value = Score::new(param[0].round() as i32, param[1].round() as i32);
+        }
+        params
+    }
+}
+
+// "chess-bot-gm tune <posiciones> [adam|local] [épocas] [salida]"
+pub fn run_tune(path: &str, method: Method, epochs: usize, output: &str) -> Result<(), String> {
+    let start = Instant::now();
+    let positions = load_positions(path)?;
+    if positions.is_empty() {
+        return Err(format!("{} no tiene posiciones válidas", path));
+    }
+    let mut tuner = Tuner::new(positions, &PARAMS);
+    println!(
+        "Posiciones: {} ({} ms)",
+        tuner.len(),
+        start.elapsed().as_millis()
+    );
+
+    let k = tuner.fit_k();
+    let initial = tuner.error(k);
+    println!("K = {:.4}, error inicial = {:.6}", k, initial);
+
+    let report = |step: usize, error: f64| {
+        println!(
+            "{} {:>5}: error = {:.6} ({} ms)",
+            match method {
+                Method::Adam => "Época",
+                Method::LocalSearch => "Pasada",
+            },
+            step,
+            error,
+            start.elapsed().as_millis()
+        )
+    };
+    let error = match method {
+        Method::Adam => tuner.adam(epochs, ADAM_LEARNING_RATE, report),
+        Method::LocalSearch => tuner.local_search(epochs, report),
+    };
+    println!("Error final = {:.6} (inicial {:.6})", error, initial);
+
+    fs::write(output, tuner.to_params().rust_source())
+        .map_err(|e| format!("No se pudo escribir {}: {}", output, e))?;
+    println!("Parámetros escritos en {}", output);
+    Ok(())
+}
@@ -35,6 +35,19 @@ impl Color {
     }
 }
 
+// Fila vista desde el bando: 0 es la primera fila propia
+pub fn relative_rank(square: Square, color: Color) -> usize {
+    match color {
+        Color::White => square / 8,
+        Color::Black => 7 - square / 8,
+    }
+}
+
+// Distancia de rey: las jugadas que necesita un rey para ir de `a` a `b`
+pub fn distance(a: Square, b: Square) -> usize {
+    (a / 8).abs_diff(b / 8).max((a % 8).abs_diff(b % 8))
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum PieceType {
     Pawn,
@@ -63,8 +76,12 @@ pub struct Board {
     pub turn: Color,
     pub castling_rights: CastlingRights,
     pub en_passant_target: Option<Square>,
+    pub hash: u64,
+    // Solo los peones: no cambia al mover piezas
+    pub pawn_hash: u64,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct Move {
     pub from: Square,
     pub to: Square,
@@ -0,0 +1,133 @@
+use crate::types::{Board, CastlingRights, Color, Piece, PieceType, Square};
+
+// Claves aleatorias para el hash Zobrist de una posición.
+// Se generan en tiempo de compilación con splitmix64 y una semilla fija,
+// así el hash de una posición es siempre el mismo entre ejecuciones.
+pub struct ZobristKeys {
+    pub pieces: [[u64; 64]; 12],
+    pub castling: [u64; 16],
+    pub en_passant: [u64; 8],
+    pub side: u64,
+}
+
+const fn splitmix64(state: u64) -> (u64, u64) {
+    let state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    (state, z ^ (z >> 31))
+}
+
+const fn generate_keys() -> ZobristKeys {
+    let mut keys = ZobristKeys {
+        pieces: [[0; 64]; 12],
+        castling: [0; 16],
+        en_passant: [0; 8],
+        side: 0,
+    };
+    let mut state = 0x2545_F491_4F6C_DD1D;
+
+    let mut piece = 0;
+    while piece < 12 {
+        let mut square = 0;
+        while square < 64 {
+            let (next, key) = splitmix64(state);
+            state = next;
+            keys.pieces[piece][square] = key;
+            square += 1;
+        }
+        piece += 1;
+    }
+
+    let mut i = 0;
+    while i < 16 {
+        let (next, key) = splitmix64(state);
+        state = next;
+        keys.castling[i] = key;
+        i += 1;
+    }
+
+    let mut file = 0;
+    while file < 8 {
+        let (next, key) = splitmix64(state);
+        state = next;
+        keys.en_passant[file] = key;
+        file += 1;
+    }
+
+    let (_, key) = splitmix64(state);
+    keys.side = key;
+
+    keys
+}
+
+pub static KEYS: ZobristKeys = generate_keys();
+
+pub fn piece_index(piece: Piece) -> usize {
+    let type_index = match piece.piece_type {
+        PieceType::Pawn => 0,
+        PieceType::Knight => 1,
+        PieceType::Bishop => 2,
+        PieceType::Rook => 3,
+        PieceType::Queen => 4,
+        PieceType::King => 5,
+    };
+    match piece.color {
+        Color::White => type_index,
+        Color::Black => type_index + 6,
+    }
+}
+
+pub fn piece_key(piece: Piece, square: Square) -> u64 {
+    KEYS.pieces[piece_index(piece)][square]
+}
+
+pub fn castling_key(rights: &CastlingRights) -> u64 {
+    let index = rights.white_kingside as usize
+        | (rights.white_queenside as usize) << 1
+        | (rights.black_kingside as usize) << 2
+        | (rights.black_queenside as usize) << 3;
+    KEYS.castling[index]
+}
+
+pub fn en_passant_key(square: Square) -> u64 {
+    KEYS.en_passant[square % 8]
+}
+
+// Calcula el hash desde cero. make_move lo mantiene de forma incremental.
+pub fn compute_hash(board: &Board) -> u64 {
+    let mut hash = 0;
+
+    for (square, piece) in board.squares.iter().enumerate() {
+        if let Some(piece) = piece {
+            hash ^= piece_key(*piece, square);
+        }
+    }
+
+    hash ^= castling_key(&board.castling_rights);
+
+    if let Some(ep) = board.en_passant_target {
+        hash ^= en_passant_key(ep);
+    }
+
+    if board.turn == Color::Black {
+        hash ^= KEYS.side;
+    }
+
+    hash
+}
+
+// Hash de la estructura de peones: las mismas claves, solo de los peones
+pub fn compute_pawn_hash(board: &Board) -> u64 {
+    let mut hash = 0;
+
+    for (square, piece) in board.squares.iter().enumerate() {
+        if let Some(piece) = piece
+            && piece.piece_type == PieceType::Pawn
+        {
+            hash ^= piece_key(*piece, square);
+        }
+    }
+
+    hash
+}